@@ -0,0 +1,75 @@
+//! `acommit --amend`: a new message for the last commit, describing what it
+//! changed together with anything staged since.
+
+use dialoguer::console::Term;
+use crate::git;
+use crate::prompt::Changes;
+use crate::trailers;
+
+pub async fn run(args: &[String], git_tail: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = crate::parse_args(args).await?;
+
+    let old_message = git::git(&["log", "-1", "--format=%B"]).map_err(|_| "There is no commit to amend")?;
+    let staged = !git::git(&["diff", "--cached", "--name-only"])?.trim().is_empty();
+    let changes = Changes::against(&git::head_parent()?)?;
+    if changes.is_empty() {
+        return Err("The last commit and the staged changes are empty, there is nothing to describe".into());
+    }
+
+    if staged {
+        println!("📝 Changes in the last commit, plus staged changes:");
+    } else {
+        println!("📝 Changes in the last commit:");
+    }
+    for line in changes.files.lines().take(10) {
+        println!("  {}", line);
+    }
+
+    let commit_options = crate::commit_options(args, git_tail)?;
+
+    println!("🤖 Generating commit message with AI...");
+    // Sign-offs and co-authors of the original stay
+    let kept = trailers::parse(&old_message)?;
+    let commit_message = crate::compose_message(&settings, args, &changes, &kept).await?;
+
+    println!();
+    print_side_by_side(&old_message, &commit_message);
+    println!();
+
+    if !crate::confirm("Amend the last commit with the new message?")? {
+        println!("❌ Amend cancelled");
+        return Ok(());
+    }
+
+    println!("💾 Amending commit...");
+    let mut options = vec!["--amend".to_string()];
+    options.extend(commit_options);
+    git::commit(&commit_message, &options)?;
+    println!("✅ Successfully amended with message: {}", commit_message);
+    Ok(())
+}
+
+/// Prints two messages in columns that fit the terminal.
+pub fn print_side_by_side(old: &str, new: &str) {
+    let (_, columns) = Term::stdout().size();
+    let width = (usize::from(columns).saturating_sub(3) / 2).max(20);
+    let old: Vec<&str> = old.trim_end().lines().collect();
+    let new: Vec<&str> = new.trim_end().lines().collect();
+
+    println!("{:<width$} │ New message", "Old message");
+    println!("{}─┼─{}", "─".repeat(width), "─".repeat(width));
+    for i in 0..old.len().max(new.len()) {
+        let left = fit(old.get(i).copied().unwrap_or(""), width);
+        let right = fit(new.get(i).copied().unwrap_or(""), width);
+        println!("{:<width$} │ {}", left, right);
+    }
+}
+
+// Long lines are cut rather than wrapped so the columns stay aligned
+fn fit(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", cut)
+}
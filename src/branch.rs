@@ -0,0 +1,156 @@
+//! `acommit branch`: a branch name for a task description or the
+//! uncommitted changes, following the configured pattern.
+
+use crate::config::{BranchConfig, Config};
+use crate::conventional;
+use crate::git;
+use crate::prompt::{self, Changes};
+use crate::providers::{self, Prompt};
+use crate::ticket;
+
+// Types offered when the conventions use plain subjects
+const DEFAULT_TYPES: [&str; 6] = ["feat", "fix", "docs", "refactor", "test", "chore"];
+
+// Longer slugs make unwieldy branch names
+const MAX_SLUG_LENGTH: usize = 40;
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let description = args.get(2).filter(|a| !a.starts_with("--")).cloned();
+
+    let settings = crate::parse_args(args).await?;
+
+    let ticket = match crate::ticket_arg(args)? {
+        Some(ticket) => Some(ticket),
+        None => match &description {
+            Some(description) => ticket::find(&settings.config, description)?,
+            None => None,
+        },
+    };
+
+    let task = match &description {
+        Some(description) => format!("this task: {}", description),
+        None => {
+            let changes = Changes::collect(true)?;
+            if changes.is_empty() {
+                return Err("Describe the task, e.g. acommit branch \"add csv export\", or make some changes to name the branch after".into());
+            }
+            println!("📝 Naming the branch after the uncommitted changes");
+            format!(
+                "these uncommitted changes:\n\n{}\n\n{}",
+                changes.files.trim(), prompt::truncate_diff(&changes.diff)
+            )
+        },
+    };
+
+    let conventions = settings.config.conventions();
+    let types: Vec<String> = if conventions.types.is_empty() {
+        DEFAULT_TYPES.iter().map(|t| t.to_string()).collect()
+    } else {
+        conventions.types.clone()
+    };
+
+    println!("🤖 Generating branch name with AI...");
+    let answer = providers::generate(&settings.provider, &branch_prompt(&task, &types)).await?;
+    let answer = conventional::clean_output(&answer);
+    let subject = answer.lines().next().unwrap_or("");
+    let (commit_type, _, _, summary) = conventional::parse_header(subject)
+        .map_err(|_| format!("The model answered '{}' instead of '<type>: <description>'", subject))?;
+    let commit_type = commit_type.to_lowercase();
+    if !types.contains(&commit_type) {
+        return Err(format!("The model picked the type '{}', which isn't one of {}", commit_type, types.join(", ")).into());
+    }
+
+    let name = branch_name(&settings.config, &commit_type, ticket.as_deref(), &summary)?;
+    if git::git(&["check-ref-format", "--branch", &name]).is_err() {
+        return Err(format!("'{}' is not a valid branch name, check the branch pattern in the config", name).into());
+    }
+    if git::current_branch().as_deref() == Some(name.as_str()) {
+        println!("✅ Already on {}", name);
+        return Ok(());
+    }
+    let exists = git::git(&["rev-parse", "--verify", "-q", &format!("refs/heads/{}", name)]).is_ok();
+
+    println!("🌿 Branch name: {}", name);
+    let question = if exists {
+        "The branch already exists. Switch to it?"
+    } else {
+        "Create the branch and switch to it?"
+    };
+    if !crate::confirm(question)? {
+        println!("❌ Branch not created");
+        return Ok(());
+    }
+
+    if exists {
+        git::git(&["switch", &name])?;
+        println!("✅ Switched to {}", name);
+    } else {
+        // Uncommitted changes come along to the new branch
+        git::git(&["switch", "-c", &name])?;
+        println!("✅ Created and switched to {}", name);
+    }
+    Ok(())
+}
+
+fn branch_prompt(task: &str, types: &[String]) -> Prompt {
+    Prompt::user(format!(
+        "Suggest a git branch for {}\n\n\
+        Answer with a single line '<type>: <description>', where:\n\
+        - type is one of: {}\n\
+        - description is 2 to 5 English words saying what the branch does, in the imperative (\"add csv export\")\n\
+        - there are no ticket IDs\n\
+        Only return that line, nothing else.",
+        task, types.join(", ")
+    ))
+}
+
+/// Fills in the configured pattern. Separators next to an empty ticket are
+/// dropped, so `feat/{{ticket}}-{{slug}}` becomes `feat/add-csv-export`.
+fn branch_name(config: &Config, commit_type: &str, ticket: Option<&str>, summary: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let default = BranchConfig::default();
+    let pattern = &config.branch.as_ref().unwrap_or(&default).pattern;
+    let mut slug = slugify(summary);
+    // The ticket is in the pattern already
+    if let Some(ticket) = ticket {
+        let ticket_slug = slugify(ticket);
+        slug = slug.trim_start_matches(&ticket_slug).trim_start_matches('-').to_string();
+    }
+    let variables = [
+        ("type", commit_type.to_string()),
+        ("ticket", ticket.unwrap_or_default().to_string()),
+        ("slug", slug),
+    ];
+    let name = prompt::render(pattern, &variables).map_err(|e| format!("{} (branch pattern)", e))?;
+
+    let segments: Vec<String> = name
+        .split('/')
+        .map(|segment| {
+            let mut segment = segment.trim_matches(['-', '_', '.']).to_string();
+            while segment.contains("--") {
+                segment = segment.replace("--", "-");
+            }
+            segment
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    Ok(segments.join("/"))
+}
+
+/// Lowercase words joined by `-`, cut at a word boundary.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase);
+    for word in words {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > MAX_SLUG_LENGTH {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word);
+    }
+    slug
+}
@@ -0,0 +1,219 @@
+//! `acommit bump`: the semantic version bump the commits since the last tag
+//! call for, optionally tagging the new version with an AI-written message.
+
+use std::fmt;
+use crate::changelog;
+use crate::conventional;
+use crate::git;
+use crate::prompt::{self, Changes};
+use crate::providers::{self, Prompt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bump::Patch => write!(f, "patch"),
+            Bump::Minor => write!(f, "minor"),
+            Bump::Major => write!(f, "major"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl Version {
+    /// Parses `1.2.3` or `v1.2.3`; pre-release and build suffixes are dropped.
+    fn parse(tag: &str) -> Option<Version> {
+        let version = tag.strip_prefix('v').unwrap_or(tag);
+        let version = version.split(['-', '+']).next()?;
+        let mut parts = version.split('.').map(|p| p.parse::<u64>().ok());
+        let version = Version { major: parts.next()??, minor: parts.next()??, patch: parts.next()?? };
+        if parts.next().is_some() { None } else { Some(version) }
+    }
+
+    fn bumped(self, bump: Bump) -> Version {
+        match bump {
+            Bump::Major => Version { major: self.major + 1, minor: 0, patch: 0 },
+            Bump::Minor => Version { major: self.major, minor: self.minor + 1, patch: 0 },
+            Bump::Patch => Version { patch: self.patch + 1, ..self },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let ask_api = args.iter().any(|a| a == "--api");
+    let create_tag = args.iter().any(|a| a == "--tag");
+
+    let (last_tag, current) = match last_version_tag()? {
+        Some((tag, version)) => (Some(tag), version),
+        None => (None, Version { major: 0, minor: 0, patch: 0 }),
+    };
+    let range = match &last_tag {
+        Some(tag) => format!("{}..HEAD", tag),
+        None => "HEAD".to_string(),
+    };
+    let since = last_tag.as_deref().unwrap_or("the first commit");
+
+    // Only the conventions are needed to read the commits, not a working provider
+    let conventions = crate::load_config_from_args(args)?.unwrap_or_default().conventions();
+    let commits = changelog::collect(&range, &conventions)?;
+    if commits.is_empty() {
+        println!("✅ No commits since {}, there is nothing to release", since);
+        return Ok(());
+    }
+
+    let breaking = commits.iter().filter(|c| c.breaking).count();
+    let features = commits.iter().filter(|c| c.commit_type.as_deref() == Some("feat")).count();
+    let fixes = commits.iter().filter(|c| c.commit_type.as_deref() == Some("fix")).count();
+    println!(
+        "🔖 {} commit(s) since {}: {} breaking, {} feature(s), {} fix(es)",
+        commits.len(), since, breaking, features, fixes
+    );
+    let mut bump = if breaking > 0 {
+        Bump::Major
+    } else if features > 0 {
+        Bump::Minor
+    } else {
+        Bump::Patch
+    };
+
+    // The suggestion itself needs no model, so neither does it need a config
+    let settings = if ask_api || create_tag {
+        let settings = crate::parse_args(args).await?;
+        Some(settings)
+    } else {
+        None
+    };
+
+    if let Some(settings) = settings.as_ref().filter(|_| ask_api) {
+        let base = match &last_tag {
+            Some(tag) => tag.clone(),
+            None => git::empty_tree()?,
+        };
+        let changes = Changes::between(&base, "HEAD")?;
+        println!("🤖 Asking the model about the public API changes...");
+        let answer = providers::generate(&settings.provider, &api_prompt(&changes)).await?;
+        match parse_api_answer(&answer) {
+            Some((api_bump, reason)) => {
+                println!("🔎 The model suggests a {} bump: {}", api_bump, reason);
+                if api_bump > bump {
+                    println!("⚠️  That is more than the commit messages say, going with {}", api_bump);
+                    bump = api_bump;
+                }
+            },
+            None => println!("⚠️  Could not make sense of the model's answer, going by the commit messages"),
+        }
+    }
+
+    // Before 1.0.0 anything may change, so breaking changes only bump the minor version
+    if current.major == 0 && bump == Bump::Major {
+        println!("💡 Breaking changes bump the minor version while the major version is 0");
+        bump = Bump::Minor;
+    }
+
+    let next = current.bumped(bump);
+    println!("📈 Suggested bump: {} ({} → {})", bump, current, next);
+
+    let Some(settings) = settings.filter(|_| create_tag) else {
+        println!("💡 Run with --tag to create the tag");
+        return Ok(());
+    };
+
+    let prefix = match &last_tag {
+        Some(tag) if !tag.starts_with('v') => "",
+        _ => "v",
+    };
+    let tag = format!("{}{}", prefix, next);
+    let messages = git::commit_messages(&range)?;
+    println!("🤖 Writing the tag message with AI...");
+    let answer = providers::generate(&settings.provider, &tag_prompt(&settings.config, &tag, &messages)).await?;
+    let message = conventional::strip_think_blocks(&answer).trim().to_string();
+    if message.is_empty() {
+        return Err("The model returned an empty tag message".into());
+    }
+
+    println!("📋 Tag message for {}:\n{}\n", tag, message);
+    if !crate::confirm(&format!("Create annotated tag {} on HEAD?", tag))? {
+        println!("❌ Tag not created");
+        return Ok(());
+    }
+
+    git::git_with_input(&["tag", "-a", &tag, "-F", "-"], &message)?;
+    println!("✅ Created tag {}", tag);
+    println!("💡 Push it with `git push origin {}`", tag);
+    Ok(())
+}
+
+/// The highest version tag HEAD contains. Other tags, such as deploy markers,
+/// are skipped.
+fn last_version_tag() -> Result<Option<(String, Version)>, Box<dyn std::error::Error>> {
+    let tags = git::git(&["tag", "--merged", "HEAD"])?;
+    Ok(tags
+        .lines()
+        .map(str::trim)
+        .filter_map(|tag| Some((tag.to_string(), Version::parse(tag)?)))
+        .max_by_key(|(_, v)| (v.major, v.minor, v.patch)))
+}
+
+fn api_prompt(changes: &Changes) -> Prompt {
+    Prompt::user(format!(
+        "Decide which semantic version bump this diff calls for, judging only its effect on the project's public API (exported functions, types, command line options, config formats, HTTP endpoints):\n\
+        - MAJOR if it removes or changes something users of the public API rely on\n\
+        - MINOR if it adds to the public API without breaking it\n\
+        - PATCH if the public API is unchanged\n\n\
+        Changed files:\n{}\n\nDiff:\n{}\n\n\
+        Answer with MAJOR, MINOR or PATCH on the first line and a one-sentence reason on the second, nothing else.",
+        changes.files.trim(), prompt::truncate_diff(&changes.diff)
+    ))
+}
+
+/// The bump and reason from the model's answer to `api_prompt`.
+fn parse_api_answer(answer: &str) -> Option<(Bump, String)> {
+    let answer = conventional::strip_think_blocks(answer);
+    let mut lines = answer.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.next()?;
+    let verdict = first.split_whitespace().next()?.trim_matches(|c: char| !c.is_ascii_alphabetic()).to_uppercase();
+    let bump = match verdict.as_str() {
+        "MAJOR" => Bump::Major,
+        "MINOR" => Bump::Minor,
+        "PATCH" => Bump::Patch,
+        _ => return None,
+    };
+    // The reason may follow the verdict on the same line
+    let same_line = first
+        .split_once(char::is_whitespace)
+        .map(|(_, rest)| rest.trim_start_matches([':', '-', '*', ' ']).trim())
+        .filter(|rest| !rest.is_empty());
+    let reason = same_line.or_else(|| lines.next()).unwrap_or("no reason given").to_string();
+    Some((bump, reason))
+}
+
+fn tag_prompt(config: &crate::config::Config, tag: &str, messages: &[String]) -> Prompt {
+    let commits: Vec<String> = messages.iter().map(|m| prompt::commit_item(m)).collect();
+    Prompt::user(format!(
+        "Write the message of the annotated git tag for release {} in {}, from the commits since the last release:\n\n{}\n\n\
+        Rules:\n\
+        - First line: a short title for the release, without the version number\n\
+        - Then an empty line and a short plain-text summary of the notable changes, breaking changes first\n\
+        - Don't use markdown headings\n\
+        - Only return the tag message, nothing else",
+        tag, prompt::language(config), commits.join("\n")
+    ))
+}
@@ -0,0 +1,390 @@
+//! `acommit changelog <from>..<to>`: release notes in the Keep a Changelog
+//! format, grouped by conventional commit type and rewritten by the model
+//! for the people reading them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::Serialize;
+use crate::config::Conventions;
+use crate::conventional;
+use crate::git;
+use crate::lint;
+use crate::prompt;
+use crate::providers::{self, Prompt};
+
+const USAGE: &str = "Usage: acommit changelog <from>..<to> [--format markdown|json] [--version <NAME>] [--prepend]";
+
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+const CHANGELOG_HEADER: &str = "# Changelog\n\nAll notable changes to this project will be documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n";
+
+// Keep a Changelog's sections, in the order they are printed
+const SECTIONS: [&str; 6] = ["Added", "Changed", "Deprecated", "Removed", "Fixed", "Security"];
+
+/// A commit in the range, parsed as far as it follows the conventions.
+pub struct Commit {
+    /// `None` for messages that aren't conventional commits.
+    pub commit_type: Option<String>,
+    pub scope: Option<String>,
+    pub description: String,
+    pub breaking: bool,
+}
+
+#[derive(Serialize)]
+struct Section {
+    title: String,
+    entries: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Release {
+    version: String,
+    /// `None` for unreleased changes.
+    date: Option<String>,
+    sections: Vec<Section>,
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let rev = args.get(2).filter(|a| !a.starts_with("--")).ok_or(USAGE)?;
+    let (from, to) = git::range_ends(rev)?.unwrap_or_else(|| (rev.clone(), "HEAD".to_string()));
+    // Named as given, e.g. `main...release`, rather than by the merge-base's hash
+    let range = format!("{}..{}", from, to);
+    let shown = if rev.contains("..") { rev.clone() } else { range.clone() };
+    let to = to.as_str();
+    let json = match crate::value_arg(args, "--format")?.as_deref() {
+        None | Some("markdown") | Some("md") => false,
+        Some("json") => true,
+        Some(other) => return Err(format!("Unknown changelog format '{}' (use markdown or json)", other).into()),
+    };
+    let prepend = args.iter().any(|a| a == "--prepend");
+    if prepend && json {
+        return Err(format!("--prepend writes markdown to {}, it can't be combined with --format json", CHANGELOG_FILE).into());
+    }
+
+    let (version, date) = match crate::value_arg(args, "--version")? {
+        Some(version) => (version, Some(commit_date(to)?)),
+        None => match release_tag(to) {
+            Some(version) => (version, Some(commit_date(to)?)),
+            None => ("Unreleased".to_string(), None),
+        },
+    };
+    if prepend {
+        // Checked now rather than after asking the model
+        read_changelog(&changelog_path(), &version)?;
+    }
+
+    let settings = crate::parse_args(args).await?;
+
+    let commits = collect(&range, &settings.config.conventions())?;
+    if commits.is_empty() {
+        return Err(format!("No commits in {}", shown).into());
+    }
+    let grouped = group(&commits);
+    if grouped.is_empty() {
+        return Err(format!(
+            "None of the {} commit(s) in {} are user-facing (only docs, tests, chores and the like)",
+            commits.len(), shown
+        ).into());
+    }
+
+    // Progress goes to stderr so stdout can be redirected
+    eprintln!("📜 {} commit(s) in {}, {} for the changelog", commits.len(), shown, grouped.iter().map(|(_, commits)| commits.len()).sum::<usize>());
+    eprintln!("🤖 Writing release notes with AI...");
+    let answer = providers::generate(&settings.provider, &notes_prompt(&settings.config, &grouped)).await?;
+    let sections = match parse_notes(&answer) {
+        Some(sections) => sections,
+        None => {
+            eprintln!("⚠️  The model's answer wasn't the expected JSON, using the commit descriptions instead");
+            grouped
+                .into_iter()
+                .map(|(title, commits)| Section { title: title.to_string(), entries: commits.iter().map(|c| entry(c)).collect() })
+                .collect()
+        },
+    };
+
+    let release = Release { version, date, sections };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&release)?);
+    } else if prepend {
+        let path = prepend_to_changelog(&release)?;
+        eprintln!("✅ Added {} to {}", release.version, path.display());
+    } else {
+        print!("{}", markdown(&release));
+    }
+    Ok(())
+}
+
+/// The commits in `range`, oldest first, without merges and fixups.
+pub fn collect(range: &str, conventions: &Conventions) -> Result<Vec<Commit>, Box<dyn std::error::Error>> {
+    let mut commits = Vec::new();
+    for message in git::commit_messages(range)? {
+        if lint::is_exempt(&message) {
+            continue;
+        }
+        let message = lint::strip_prefixes(&message, conventions);
+        let commit = match conventional::parse(&message) {
+            Ok(parsed) => Commit {
+                breaking: parsed.is_breaking(),
+                commit_type: Some(parsed.commit_type.to_lowercase()),
+                scope: parsed.scope,
+                description: parsed.description,
+            },
+            // A sloppy body shouldn't hide the type in the subject
+            Err(_) => {
+                let subject = message.lines().next().unwrap_or("").trim();
+                match conventional::parse_header(subject) {
+                    Ok((commit_type, scope, breaking, description)) => Commit {
+                        commit_type: Some(commit_type.to_lowercase()),
+                        scope,
+                        description,
+                        breaking: breaking || message.contains("BREAKING CHANGE:") || message.contains("BREAKING-CHANGE:"),
+                    },
+                    Err(_) => Commit { commit_type: None, scope: None, description: subject.to_string(), breaking: false },
+                }
+            },
+        };
+        commits.push(commit);
+    }
+    Ok(commits)
+}
+
+/// The Keep a Changelog section a commit type belongs in. Types that don't
+/// concern users (docs, tests, chores, CI, ...) have none.
+fn section(commit_type: Option<&str>) -> Option<&'static str> {
+    match commit_type {
+        Some("feat") => Some("Added"),
+        Some("fix") => Some("Fixed"),
+        Some("perf" | "refactor" | "revert") => Some("Changed"),
+        Some("deprecate") => Some("Deprecated"),
+        Some("remove") => Some("Removed"),
+        Some("security") => Some("Security"),
+        // Plain messages could be anything, so the model sorts them out
+        None => Some("Changed"),
+        Some(_) => None,
+    }
+}
+
+/// The commits that belong in the changelog, by section in Keep a Changelog
+/// order.
+fn group(commits: &[Commit]) -> Vec<(&'static str, Vec<&Commit>)> {
+    SECTIONS
+        .iter()
+        .map(|title| {
+            let entries: Vec<&Commit> = commits
+                .iter()
+                // Breaking changes are listed whatever their type
+                .filter(|c| section(c.commit_type.as_deref()).or(c.breaking.then_some("Changed")) == Some(title))
+                .collect();
+            (*title, entries)
+        })
+        .filter(|(_, entries)| !entries.is_empty())
+        .collect()
+}
+
+/// A commit as a changelog entry, as written in its message.
+fn entry(commit: &Commit) -> String {
+    let breaking = if commit.breaking { "**Breaking:** " } else { "" };
+    match &commit.scope {
+        Some(scope) => format!("{}**{}:** {}", breaking, scope, commit.description),
+        None => format!("{}{}", breaking, commit.description),
+    }
+}
+
+fn notes_prompt(config: &crate::config::Config, grouped: &[(&'static str, Vec<&Commit>)]) -> Prompt {
+    let mut commits = String::new();
+    for (section, entries) in grouped {
+        commits.push_str(&format!("{}:\n", section));
+        for commit in entries {
+            let kind = commit.commit_type.as_deref().unwrap_or("unknown type");
+            commits.push_str(&format!("- ({}) {}\n", kind, entry(commit)));
+        }
+    }
+    Prompt::user(format!(
+        "Write release notes in {} from these commits, grouped into Keep a Changelog sections:\n\n{}\n\
+        Rules:\n\
+        - Write each entry for users of the project, not its developers, in one short sentence\n\
+        - Merge entries that describe the same change and drop ones users won't notice\n\
+        - Move an entry to a better section if it is in the wrong one (sections: {})\n\
+        - Keep the \"**Breaking:**\" marker on breaking changes\n\
+        - Answer with a JSON object mapping section names to arrays of entries, e.g. {{\"Added\": [\"...\"]}}, and nothing else",
+        prompt::language(config), commits, SECTIONS.join(", ")
+    ))
+}
+
+/// The sections from the model's JSON answer, in Keep a Changelog order.
+fn parse_notes(answer: &str) -> Option<Vec<Section>> {
+    let answer = conventional::strip_think_blocks(answer);
+    let start = answer.find('{')?;
+    let end = answer.rfind('}')?;
+    let notes: BTreeMap<String, Vec<String>> = serde_json::from_str(answer.get(start..=end)?).ok()?;
+
+    let sections: Vec<Section> = SECTIONS
+        .iter()
+        .filter_map(|title| {
+            let entries: Vec<String> = notes
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(title))
+                .map(|(_, entries)| entries.iter().map(|e| e.trim().trim_start_matches("- ").to_string()).filter(|e| !e.is_empty()).collect())?;
+            (!entries.is_empty()).then(|| Section { title: title.to_string(), entries })
+        })
+        .collect();
+    if sections.is_empty() { None } else { Some(sections) }
+}
+
+fn markdown(release: &Release) -> String {
+    let mut text = match &release.date {
+        Some(date) => format!("## [{}] - {}\n", release.version, date),
+        None => format!("## [{}]\n", release.version),
+    };
+    for section in &release.sections {
+        text.push_str(&format!("\n### {}\n\n", section.title));
+        for entry in &section.entries {
+            text.push_str(&format!("- {}\n", entry));
+        }
+    }
+    text
+}
+
+fn changelog_path() -> PathBuf {
+    git::repo_root().unwrap_or_else(|| PathBuf::from(".")).join(CHANGELOG_FILE)
+}
+
+/// `CHANGELOG.md`'s text, or the standard header when there is none yet.
+fn read_changelog(path: &PathBuf, version: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let existing = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => CHANGELOG_HEADER.to_string(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e).into()),
+    };
+    let heading = format!("## [{}]", version);
+    if existing.lines().any(|line| line.starts_with(&heading)) {
+        return Err(format!("{} already has a section for {}", path.display(), version).into());
+    }
+    Ok(existing)
+}
+
+/// Inserts the release above the newest one in `CHANGELOG.md` at the
+/// repository root, creating the file if needed.
+fn prepend_to_changelog(release: &Release) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = changelog_path();
+    let existing = read_changelog(&path, &release.version)?;
+    let text = insert_release(&existing, release);
+    fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// `existing` with the release inserted above the newest one.
+fn insert_release(existing: &str, release: &Release) -> String {
+    // Releases go below the Unreleased section, which stays on top
+    let insert_at = existing
+        .match_indices("\n## ")
+        .map(|(i, _)| i + 1)
+        .find(|&i| release.version == "Unreleased" || !existing[i..].starts_with("## [Unreleased]"))
+        .unwrap_or(existing.len());
+    let (head, tail) = existing.split_at(insert_at);
+    let separator = if head.is_empty() || head.ends_with("\n\n") { "" } else if head.ends_with('\n') { "\n" } else { "\n\n" };
+    let tail = if tail.is_empty() { String::new() } else { format!("\n{}", tail) };
+    format!("{}{}{}{}", head, separator, markdown(release), tail)
+}
+
+/// The version `rev` is tagged with, without a leading `v`.
+fn release_tag(rev: &str) -> Option<String> {
+    let tag = git::git(&["describe", "--tags", "--exact-match", rev]).ok()?;
+    let tag = tag.trim();
+    let version = tag.strip_prefix('v').filter(|v| v.starts_with(|c: char| c.is_ascii_digit())).unwrap_or(tag);
+    Some(version.to_string())
+}
+
+/// The day `rev` was committed, as `YYYY-MM-DD`.
+fn commit_date(rev: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(git::git(&["log", "-1", "--format=%cs", rev])?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str, date: Option<&str>) -> Release {
+        Release {
+            version: version.to_string(),
+            date: date.map(String::from),
+            sections: vec![Section { title: "Added".to_string(), entries: vec!["CSV export".to_string()] }],
+        }
+    }
+
+    #[test]
+    fn releases_go_below_the_unreleased_section() {
+        let existing = "# Changelog\n\n## [Unreleased]\n\n### Fixed\n\n- Crash\n\n## [1.0.0] - 2026-01-01\n\n### Added\n\n- Import\n";
+        assert_eq!(
+            insert_release(existing, &release("1.1.0", Some("2026-02-01"))),
+            "# Changelog\n\n## [Unreleased]\n\n### Fixed\n\n- Crash\n\n\
+            ## [1.1.0] - 2026-02-01\n\n### Added\n\n- CSV export\n\n\
+            ## [1.0.0] - 2026-01-01\n\n### Added\n\n- Import\n"
+        );
+    }
+
+    #[test]
+    fn unreleased_goes_above_the_newest_release() {
+        let existing = "# Changelog\n\n## [1.0.0] - 2026-01-01\n\n### Added\n\n- Import\n";
+        assert_eq!(
+            insert_release(existing, &release("Unreleased", None)),
+            "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- CSV export\n\n## [1.0.0] - 2026-01-01\n\n### Added\n\n- Import\n"
+        );
+    }
+
+    #[test]
+    fn the_first_release_goes_below_the_header() {
+        assert_eq!(
+            insert_release("# Changelog\n\nNotes about this project.", &release("0.1.0", Some("2026-02-01"))),
+            "# Changelog\n\nNotes about this project.\n\n## [0.1.0] - 2026-02-01\n\n### Added\n\n- CSV export\n"
+        );
+    }
+
+    #[test]
+    fn a_missing_changelog_starts_from_the_standard_header() {
+        let path = std::env::temp_dir().join("acommit-test-missing").join(CHANGELOG_FILE);
+        let existing = read_changelog(&path, "0.1.0").unwrap();
+        assert_eq!(existing, CHANGELOG_HEADER);
+        assert_eq!(
+            insert_release(&existing, &release("0.1.0", None)),
+            format!("{}\n## [0.1.0]\n\n### Added\n\n- CSV export\n", CHANGELOG_HEADER)
+        );
+    }
+
+    #[test]
+    fn an_existing_version_is_refused() {
+        let path = std::env::temp_dir().join(format!("acommit-test-changelog-{}.md", std::process::id()));
+        fs::write(&path, "# Changelog\n\n## [1.0.0] - 2026-01-01\n").unwrap();
+        let result = read_changelog(&path, "1.0.0");
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn notes_are_read_from_fenced_json() {
+        let sections = parse_notes("```json\n{\"Fixed\": [\"- Crash on start\"], \"added\": [\"CSV export\", \"\"]}\n```").unwrap();
+        let sections: Vec<(&str, &[String])> = sections.iter().map(|s| (s.title.as_str(), s.entries.as_slice())).collect();
+        assert_eq!(
+            sections,
+            [("Added", &["CSV export".to_string()][..]), ("Fixed", &["Crash on start".to_string()][..])]
+        );
+    }
+
+    #[test]
+    fn notes_are_read_from_json_surrounded_by_text() {
+        let answer = "<think>{\"Removed\": [\"x\"]}</think>Here are the notes:\n{\"Changed\": [\"Faster sync\"]}\nHope this helps!";
+        let sections = parse_notes(answer).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, "Changed");
+        assert_eq!(sections[0].entries, ["Faster sync"]);
+    }
+
+    #[test]
+    fn unusable_notes_are_rejected() {
+        assert!(parse_notes("No notable changes.").is_none());
+        assert!(parse_notes("{\"Added\": []}").is_none());
+        assert!(parse_notes("{\"Added\": \"not a list\"}").is_none());
+    }
+}
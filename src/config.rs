@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::git;
+use crate::interpolate;
+use crate::providers::ModelProvider;
+use crate::redact;
+
+pub const CONFIG_FILE_NAME: &str = "acommit.json";
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ProviderConfig {
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl fmt::Debug for ProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "ProviderConfig {{ model: {:?}, api_key: {}, url: {:?} }}",
+            self.model, redact::mask_opt(self.api_key.as_deref()), self.url
+        )
+    }
+}
+
+// Provider sections are optional so a config only needs the providers it uses
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Config {
+    pub default_provider: String,
+    #[serde(default)]
+    pub verbose: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<ProviderConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ollama: Option<ProviderConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai: Option<ProviderConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conventions: Option<Conventions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<PromptConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket: Option<TicketConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailers: Option<TrailersConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<BranchConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<ReviewConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SummaryConfig>,
+}
+
+/// Where `acommit summary` looks for commits (see `summary::run`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Repository paths to summarize together instead of the current one.
+    pub repositories: Vec<String>,
+}
+
+/// Reviewing the changes before generating the message (see `review::gate`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ReviewConfig {
+    /// Review on every commit, as `--review` does.
+    pub enabled: bool,
+    /// Stop the commit on findings this severe or worse.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_on: Option<Severity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// TODOs, naming, style
+    Low,
+    /// Leftover debug output, missing tests
+    Medium,
+    /// Likely bugs and security problems
+    High,
+}
+
+/// Names proposed by `acommit branch` (see `branch::branch_name`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BranchConfig {
+    /// Made of `{{type}}`, `{{ticket}}` and `{{slug}}`. Separators left
+    /// dangling by a missing ticket are dropped.
+    pub pattern: String,
+}
+
+impl Default for BranchConfig {
+    fn default() -> Self {
+        BranchConfig { pattern: "{{type}}/{{ticket}}-{{slug}}".to_string() }
+    }
+}
+
+/// Trailers added to generated messages (see `trailers::collect`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TrailersConfig {
+    /// Always add `Signed-off-by` with your git identity, as `--signoff` does.
+    pub signoff: bool,
+    /// Co-authors by alias: `{"alice": "Alice Smith <alice@example.com>"}`.
+    pub pairs: BTreeMap<String, String>,
+    /// Aliases from `pairs` to add when `--pair` isn't given.
+    pub pair_with: Vec<String>,
+    /// Static `Token: value` trailers.
+    pub custom: Vec<String>,
+}
+
+/// Finding the ticket ID in the branch name (see `ticket::from_branch`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TicketConfig {
+    /// Regex matched against the branch name; its first group, if any, is the ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    pub placement: TicketPlacement,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketPlacement {
+    /// `[PROJ-482] feat: ...`
+    #[default]
+    Prefix,
+    /// A `Refs: PROJ-482` trailer
+    Trailer,
+    /// Only told to the model
+    None,
+}
+
+/// Prompt template overriding the built-in one (see `prompt::load_template`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PromptConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Language messages are written in, "English" by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Number of earlier commit subjects shown to the model as style examples.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples_from: Option<ExampleSource>,
+}
+
+/// Which commits the style examples are taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExampleSource {
+    /// The latest commits
+    Recent,
+    /// The latest commits touching the changed files, falling back to `Recent`
+    Paths,
+}
+
+/// Rules commit messages are expected to follow.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Conventions {
+    /// Allowed conventional commit types. Empty for plain subjects without a type.
+    pub types: Vec<String>,
+    /// Allowed scopes, or `"auto"` for the repository's directories.
+    pub scopes: Scopes,
+    /// Emoji put in front of the subject, by commit type (gitmoji style).
+    pub emoji: BTreeMap<String, String>,
+    /// Require subjects to start with a `[PROJ-123]` ticket reference.
+    pub ticket_prefix: bool,
+    /// Maximum length of the first line.
+    pub max_subject_length: usize,
+    /// How the description starts.
+    pub casing: Casing,
+    /// Require the description to start with an imperative verb ("add", not "added").
+    pub imperative: bool,
+    /// Maximum length of body lines.
+    pub body_wrap: usize,
+}
+
+impl Default for Conventions {
+    fn default() -> Self {
+        Conventions {
+            types: [
+                "feat", "fix", "docs", "style", "refactor", "perf",
+                "test", "build", "ci", "chore", "revert",
+            ].iter().map(|t| t.to_string()).collect(),
+            scopes: Scopes::List(Vec::new()),
+            emoji: BTreeMap::new(),
+            ticket_prefix: false,
+            max_subject_length: 50,
+            casing: Casing::Any,
+            imperative: true,
+            body_wrap: 72,
+        }
+    }
+}
+
+impl Conventions {
+    /// Scopes a message may use; empty allows any scope.
+    pub fn allowed_scopes(&self) -> Vec<String> {
+        match &self.scopes {
+            Scopes::List(scopes) => scopes.clone(),
+            Scopes::Auto(_) => directory_scopes(),
+        }
+    }
+}
+
+/// `["api", "cli"]`, or `"auto"`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Scopes {
+    List(Vec<String>),
+    Auto(AutoScopes),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoScopes {
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Casing {
+    /// "add export", the Conventional Commits habit
+    Lower,
+    /// "Add export"
+    Sentence,
+    Any,
+}
+
+// Directories whose children are the packages of a monorepo
+const PACKAGE_DIRS: [&str; 6] = ["packages", "crates", "apps", "libs", "services", "modules"];
+
+/// Top-level directories tracked by git, plus the packages inside the usual
+/// monorepo directories (`packages/web` gives `web`).
+fn directory_scopes() -> Vec<String> {
+    let files = git::git(&["ls-files"]).unwrap_or_default();
+    let mut scopes = Vec::new();
+    for file in files.lines() {
+        let parts: Vec<&str> = file.split('/').collect();
+        if parts.len() < 2 {
+            continue;
+        }
+        scopes.push(parts[0].to_string());
+        if parts.len() > 2 && PACKAGE_DIRS.contains(&parts[0]) {
+            scopes.push(parts[1].to_string());
+        }
+    }
+    scopes.sort();
+    scopes.dedup();
+    scopes.retain(|scope| !scope.starts_with('.'));
+    scopes
+}
+
+impl Config {
+    pub fn conventions(&self) -> Conventions {
+        self.conventions.clone().unwrap_or_default()
+    }
+
+    pub fn provider_config(&self, name: &str) -> Option<&ProviderConfig> {
+        match name {
+            "gemini" => self.gemini.as_ref(),
+            "ollama" => self.ollama.as_ref(),
+            "openai" => self.openai.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn provider_config_mut(&mut self, name: &str) -> Option<&mut Option<ProviderConfig>> {
+        match name {
+            "gemini" => Some(&mut self.gemini),
+            "ollama" => Some(&mut self.ollama),
+            "openai" => Some(&mut self.openai),
+            _ => None,
+        }
+    }
+}
+
+/// Personal config shared by all repositories: `$ACOMMIT_CONFIG`, or
+/// `acommit/acommit.json` under `$XDG_CONFIG_HOME` (default `~/.config`).
+/// Used when there is no `--config` and no repository config.
+pub fn global_config_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("ACOMMIT_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("acommit").join(CONFIG_FILE_NAME))
+}
+
+/// Repository config: `acommit.json` in the current directory if present,
+/// otherwise at the repository root.
+pub fn repo_config_path() -> PathBuf {
+    let local = PathBuf::from(CONFIG_FILE_NAME);
+    if local.exists() {
+        return local;
+    }
+    match git::repo_root() {
+        Some(root) => root.join(CONFIG_FILE_NAME),
+        None => local,
+    }
+}
+
+/// The config file to use: `--config <PATH>`, else `$ACOMMIT_CONFIG`, else
+/// the repository config, else the global config. The first one found is
+/// used on its own; `None` when there is none.
+pub fn config_file(explicit: Option<&str>) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    if let Some(path) = explicit {
+        return Ok(Some(PathBuf::from(path)));
+    }
+    if let Ok(path) = env::var("ACOMMIT_CONFIG") {
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            return Err(format!("ACOMMIT_CONFIG points to a missing file: {}", path.display()).into());
+        }
+        return Ok(Some(path));
+    }
+    let repo = repo_config_path();
+    if repo.exists() {
+        return Ok(Some(repo));
+    }
+    Ok(global_config_path().filter(|path| path.exists()))
+}
+
+fn read_config_value(path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+    let config_content = fs::read_to_string(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let raw: Value = serde_json::from_str(&config_content)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(raw)
+}
+
+/// Reads a single config file as written, without expanding `${VAR}`s.
+/// Used by `--setup` so placeholders survive a rewrite.
+pub fn load_config_raw(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+    let raw = read_config_value(path)?;
+    let config: Config = serde_json::from_value(raw)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(config)
+}
+
+/// Loads a config file, expanding `${VAR}`s in its strings.
+pub fn load_config(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut raw = read_config_value(path)?;
+    interpolate::interpolate_value(&mut raw)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let config: Config = serde_json::from_value(raw)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(config)
+}
+
+pub fn config_to_provider(config: &Config, provider: Option<&str>) -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
+    let verbose = config.verbose;
+    let selected_provider = provider.unwrap_or(&config.default_provider);
+    let section = |name: &str| {
+        config.provider_config(name)
+            .ok_or_else(|| format!("Provider '{}' is not configured (run: acommit --setup)", name))
+    };
+
+    let provider = match selected_provider {
+        "gemini" => {
+            let gemini = section("gemini")?;
+            let api_key = gemini.api_key.clone()
+                .or_else(|| env::var("GEMINI_API_KEY").ok())
+                .ok_or("Gemini API key is required")?;
+            ModelProvider::Gemini {
+                api_key,
+                model: gemini.model.clone(),
+            }
+        },
+        "ollama" => {
+            let ollama = section("ollama")?;
+            let base_url = ollama.url.clone()
+                .unwrap_or_else(|| "http://localhost:11434".to_string());
+            ModelProvider::Ollama {
+                base_url,
+                model: ollama.model.clone(),
+            }
+        },
+        "openai" => {
+            let openai = section("openai")?;
+            let base_url = openai.url.clone()
+                .ok_or("OpenAI URL is required")?;
+            let api_key = openai.api_key.clone()
+                .or_else(|| env::var("OPENAI_API_KEY").ok());
+            ModelProvider::OpenAI {
+                base_url,
+                api_key,
+                model: openai.model.clone(),
+            }
+        },
+        _ => return Err(format!("Unknown provider: {}", selected_provider).into()),
+    };
+
+    // Registered here so no caller can forget to keep the key out of its output
+    if let Some(key) = provider.api_key() {
+        redact::register(key);
+    }
+    Ok((provider, verbose))
+}
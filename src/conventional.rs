@@ -0,0 +1,388 @@
+//! Conventional Commits 1.0 parsing, and cleanup of what models return.
+
+use std::fmt;
+
+/// A `token: value` (or `token #value`) trailer at the end of a message.
+#[derive(Debug, Clone)]
+pub struct Footer {
+    pub token: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitMessage {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    /// Marked with `!` in the header.
+    pub breaking: bool,
+    pub description: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+}
+
+impl CommitMessage {
+    /// Breaking either by `!` or by a `BREAKING CHANGE` footer.
+    pub fn is_breaking(&self) -> bool {
+        self.breaking || self.footers.iter().any(|f| is_breaking_token(&f.token))
+    }
+
+    pub fn header(&self) -> String {
+        let scope = self.scope.as_ref().map(|s| format!("({})", s)).unwrap_or_default();
+        let bang = if self.breaking { "!" } else { "" };
+        format!("{}{}{}: {}", self.commit_type, scope, bang, self.description)
+    }
+
+    /// Fixes the small slips models make that don't change the meaning:
+    /// type casing, stray whitespace and a trailing period.
+    pub fn normalize(&mut self) {
+        self.commit_type = self.commit_type.to_lowercase();
+        self.scope = self.scope.as_ref().map(|s| s.trim().to_string());
+        let description = self.description.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.description = description.trim_end_matches('.').to_string();
+    }
+}
+
+impl fmt::Display for CommitMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header())?;
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{}", body)?;
+        }
+        if !self.footers.is_empty() {
+            writeln!(f)?;
+            for footer in &self.footers {
+                let separator = if footer.value.starts_with('#') { " " } else { ": " };
+                write!(f, "\n{}{}{}", footer.token, separator, footer.value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_breaking_token(token: &str) -> bool {
+    token == "BREAKING CHANGE" || token == "BREAKING-CHANGE"
+}
+
+/// Parses a full commit message. The error says which rule was broken.
+pub fn parse(message: &str) -> Result<CommitMessage, String> {
+    let message = message.trim();
+    let mut paragraphs = split_paragraphs(message);
+    if paragraphs.is_empty() {
+        return Err("Message is empty".to_string());
+    }
+
+    let header_paragraph = paragraphs.remove(0);
+    let mut header_lines = header_paragraph.lines();
+    let header = header_lines.next().unwrap_or("");
+    if header_lines.next().is_some() {
+        return Err("The body must be separated from the subject by a blank line".to_string());
+    }
+    let (commit_type, scope, breaking, description) = parse_header(header)?;
+
+    // Footers are the last paragraph, when it starts with a `token: value` line
+    let mut footers = Vec::new();
+    if let Some(last) = paragraphs.last() {
+        if footer_start(last.lines().next().unwrap_or("")).is_some() {
+            footers = parse_footers(last)?;
+            paragraphs.pop();
+        }
+    }
+
+    let body = if paragraphs.is_empty() { None } else { Some(paragraphs.join("\n\n")) };
+
+    Ok(CommitMessage { commit_type, scope, breaking, description, body, footers })
+}
+
+/// Parses `type(scope)!: description`.
+pub fn parse_header(header: &str) -> Result<(String, Option<String>, bool, String), String> {
+    let (prefix, description) = header
+        .split_once(": ")
+        .or_else(|| header.strip_suffix(':').map(|p| (p, "")))
+        .ok_or("Subject must look like '<type>(<scope>): <description>'")?;
+
+    let (prefix, breaking) = match prefix.strip_suffix('!') {
+        Some(prefix) => (prefix, true),
+        None => (prefix, false),
+    };
+
+    let (commit_type, scope) = match prefix.split_once('(') {
+        Some((commit_type, rest)) => {
+            let scope = rest
+                .strip_suffix(')')
+                .ok_or("Scope must be closed with ')' before the ':'")?;
+            if scope.trim().is_empty() || scope.contains(['(', ')']) {
+                return Err(format!("Invalid scope '({})'", scope));
+            }
+            (commit_type, Some(scope.to_string()))
+        },
+        None => (prefix, None),
+    };
+
+    if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err("Subject must look like '<type>(<scope>): <description>'".to_string());
+    }
+
+    let description = description.trim();
+    if description.is_empty() {
+        return Err("Subject has no description after the type".to_string());
+    }
+
+    Ok((commit_type.to_string(), scope, breaking, description.to_string()))
+}
+
+fn split_paragraphs(message: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in message.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join("\n"));
+                current.clear();
+            }
+        } else {
+            current.push(line.trim_end());
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current.join("\n"));
+    }
+    paragraphs
+}
+
+/// Token and value of a line that starts a footer.
+fn footer_start(line: &str) -> Option<(&str, &str)> {
+    // Matched case-insensitively so a lowercase spelling can be reported
+    for breaking in ["BREAKING CHANGE", "BREAKING-CHANGE"] {
+        if let (Some(token), Some(rest)) = (line.get(..breaking.len()), line.get(breaking.len()..)) {
+            if token.eq_ignore_ascii_case(breaking) {
+                if let Some(value) = rest.strip_prefix(": ") {
+                    return Some((token, value));
+                }
+            }
+        }
+    }
+
+    let end = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))?;
+    let (token, rest) = line.split_at(end);
+    if token.is_empty() || !token.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    rest.strip_prefix(": ")
+        .or_else(|| rest.strip_prefix(" #").map(|_| &rest[1..]))
+        .map(|value| (token, value))
+}
+
+/// Whether `line` starts a footer such as `Signed-off-by: ...`,
+/// `BREAKING CHANGE: ...` or `Fixes #12`.
+pub fn is_footer_line(line: &str) -> bool {
+    footer_start(line).is_some()
+}
+
+fn parse_footers(paragraph: &str) -> Result<Vec<Footer>, String> {
+    let mut footers: Vec<Footer> = Vec::new();
+    for line in paragraph.lines() {
+        match footer_start(line) {
+            Some((token, value)) => {
+                if is_breaking_token(&token.to_uppercase()) && !is_breaking_token(token) {
+                    return Err("BREAKING CHANGE must be written in uppercase".to_string());
+                }
+                footers.push(Footer { token: token.to_string(), value: value.to_string() });
+            },
+            // Footer values may continue on the following lines
+            None => match footers.last_mut() {
+                Some(footer) => {
+                    footer.value.push('\n');
+                    footer.value.push_str(line);
+                },
+                None => return Err(format!("Invalid footer line: {}", line)),
+            },
+        }
+    }
+    Ok(footers)
+}
+
+/// Strips what models like to wrap around a commit message: `<think>`
+/// blocks, markdown fences, "Commit message:" style preambles, quotes and
+/// bold markers.
+pub fn clean_output(raw: &str) -> String {
+    let text = strip_think_blocks(raw);
+
+    let mut lines: Vec<String> = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .map(|line| line.trim_end().to_string())
+        .collect();
+
+    // Drop preambles such as "Here is a commit message for these changes:"
+    while let Some(first) = lines.iter().position(|l| !l.trim().is_empty()) {
+        let line = strip_wrapping(lines[first].trim());
+        let lower = line.to_lowercase();
+        let is_preamble = (line.ends_with(':') && parse_header(&line).is_err())
+            || lower.starts_with("here is")
+            || lower.starts_with("here's");
+        if !is_preamble {
+            break;
+        }
+        // "Here is the commit message: feat: add x" keeps the part after the preamble
+        let inline = line.split_once(": ").map(|(_, rest)| strip_wrapping(rest));
+        match inline.filter(|rest| parse_header(rest).is_ok()) {
+            Some(rest) => {
+                lines[first] = rest;
+                break;
+            },
+            None => {
+                lines.drain(..=first);
+            },
+        }
+    }
+
+    // Leading blank lines go, and the subject loses any quotes and labels
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    let mut lines: Vec<String> = lines.split_off(start);
+    if let Some(subject) = lines.first_mut() {
+        let mut cleaned = strip_wrapping(subject.trim());
+        for label in ["commit message:", "commit:", "subject:", "title:"] {
+            if cleaned.to_lowercase().starts_with(label) {
+                cleaned = strip_wrapping(cleaned[label.len()..].trim());
+            }
+        }
+        *subject = cleaned;
+    }
+
+    let mut message = lines.join("\n");
+    while message.contains("\n\n\n") {
+        message = message.replace("\n\n\n", "\n\n");
+    }
+    strip_wrapping(message.trim())
+}
+
+/// Removes the `<think>` blocks reasoning models put before their answer.
+pub fn strip_think_blocks(raw: &str) -> String {
+    let mut text = raw.to_string();
+    for (open, close) in [("<think>", "</think>"), ("<thinking>", "</thinking>")] {
+        while let Some(start) = text.find(open) {
+            match text[start..].find(close) {
+                Some(end) => text.replace_range(start..start + end + close.len(), ""),
+                // An unterminated block is all reasoning, no answer
+                None => text.truncate(start),
+            }
+        }
+    }
+    text
+}
+
+/// Removes quotes, backticks or bold markers around the whole text.
+pub fn strip_wrapping(text: &str) -> String {
+    let mut text = text.trim();
+    loop {
+        let stripped = [("\"", "\""), ("'", "'"), ("`", "`"), ("“", "”"), ("**", "**")]
+            .iter()
+            .find_map(|(open, close)| {
+                text.strip_prefix(open)
+                    .and_then(|t| t.strip_suffix(close))
+                    .filter(|t| !t.is_empty())
+            });
+        match stripped {
+            Some(inner) => text = inner.trim(),
+            None => return text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_header_body_and_footers() {
+        let message = parse("feat(api): add export\n\nExports the data as CSV.\n\nRefs: PROJ-1\nReviewed-by: Alice").unwrap();
+        assert_eq!(message.commit_type, "feat");
+        assert_eq!(message.scope.as_deref(), Some("api"));
+        assert_eq!(message.description, "add export");
+        assert_eq!(message.body.as_deref(), Some("Exports the data as CSV."));
+        let footers: Vec<(&str, &str)> = message.footers.iter().map(|f| (f.token.as_str(), f.value.as_str())).collect();
+        assert_eq!(footers, [("Refs", "PROJ-1"), ("Reviewed-by", "Alice")]);
+        assert!(!message.is_breaking());
+    }
+
+    #[test]
+    fn hash_footers_and_continuation_lines() {
+        let message = parse("fix: handle empty input\n\nFixes #12\nNote: first line\n  continued").unwrap();
+        assert_eq!(message.body, None);
+        assert_eq!(message.footers[0].token, "Fixes");
+        assert_eq!(message.footers[0].value, "#12");
+        assert_eq!(message.footers[1].value, "first line\n  continued");
+        assert_eq!(message.to_string(), "fix: handle empty input\n\nFixes #12\nNote: first line\n  continued");
+    }
+
+    #[test]
+    fn breaking_marker_in_header() {
+        let message = parse("refactor(core)!: drop the v1 config").unwrap();
+        assert!(message.breaking);
+        assert!(message.is_breaking());
+        assert_eq!(message.header(), "refactor(core)!: drop the v1 config");
+    }
+
+    #[test]
+    fn breaking_change_footers() {
+        assert!(parse("feat: x\n\nBREAKING CHANGE: the config moved").unwrap().is_breaking());
+        assert!(parse("feat: x\n\nBREAKING-CHANGE: the config moved").unwrap().is_breaking());
+        let error = parse("feat: x\n\nbreaking change: the config moved").unwrap_err();
+        assert!(error.contains("uppercase"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        assert!(parse("").is_err());
+        assert!(parse("add export").is_err());
+        assert!(parse("feat: ").is_err());
+        assert!(parse("feat(: x").is_err());
+        assert!(parse("feat(): x").is_err());
+        assert!(parse("feat: x\nbody without a blank line").is_err());
+    }
+
+    #[test]
+    fn normalize_fixes_small_slips() {
+        let mut message = parse("Feat( ui ): add  dark mode.").unwrap();
+        message.normalize();
+        assert_eq!(message.header(), "feat(ui): add dark mode");
+    }
+
+    #[test]
+    fn clean_output_strips_think_blocks() {
+        assert_eq!(clean_output("<think>\nThe diff adds a flag.\n</think>\nfeat: add --json flag"), "feat: add --json flag");
+        assert_eq!(clean_output("<thinking>hmm</thinking>fix: typo"), "fix: typo");
+        assert_eq!(strip_think_blocks("<think>never finished"), "");
+    }
+
+    #[test]
+    fn clean_output_strips_code_fences() {
+        assert_eq!(
+            clean_output("```text\nfeat: add export\n\nWrites CSV files.\n```"),
+            "feat: add export\n\nWrites CSV files."
+        );
+        assert_eq!(clean_output("```\nfix: handle empty input\n```"), "fix: handle empty input");
+    }
+
+    #[test]
+    fn clean_output_strips_preambles_and_quotes() {
+        assert_eq!(clean_output("Here is a commit message for these changes:\n\n\"feat: add export\""), "feat: add export");
+        assert_eq!(clean_output("Here is the commit message: `fix: typo`"), "fix: typo");
+        assert_eq!(clean_output("Commit message: docs: update readme"), "docs: update readme");
+        assert_eq!(clean_output("**refactor: split parser**"), "refactor: split parser");
+        assert_eq!(clean_output("Subject: chore: bump deps"), "chore: bump deps");
+    }
+
+    #[test]
+    fn clean_output_keeps_body_and_footers() {
+        let raw = "feat: add export\n\n\n\nWrites CSV files.\n\nRefs: PROJ-1";
+        assert_eq!(clean_output(raw), "feat: add export\n\nWrites CSV files.\n\nRefs: PROJ-1");
+    }
+
+    #[test]
+    fn strip_wrapping_only_strips_matching_pairs() {
+        assert_eq!(strip_wrapping("\"'feat: x'\""), "feat: x");
+        assert_eq!(strip_wrapping("“fix: y”"), "fix: y");
+        assert_eq!(strip_wrapping("\"unbalanced"), "\"unbalanced");
+    }
+}
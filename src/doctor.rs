@@ -0,0 +1,241 @@
+use std::fs;
+use crate::config::{self, Config};
+use crate::git;
+use crate::providers::{self, ApiError, ModelProvider};
+use crate::redact;
+
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Info,
+}
+
+struct Report {
+    passed: usize,
+    warnings: usize,
+    failed: usize,
+}
+
+impl Report {
+    fn check(&mut self, status: Status, name: &str, detail: &str) {
+        let icon = match status {
+            Status::Pass => { self.passed += 1; "✅" },
+            Status::Warn => { self.warnings += 1; "⚠️ " },
+            Status::Fail => { self.failed += 1; "❌" },
+            Status::Info => "➖",
+        };
+        println!("{} {:<12} {}", icon, name, redact::redact(detail));
+    }
+}
+
+/// `acommit doctor`: checks everything acommit depends on and prints a
+/// pass/fail report instead of stopping at the first error.
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🩺 acommit doctor");
+    println!();
+
+    let mut report = Report { passed: 0, warnings: 0, failed: 0 };
+
+    let git_ok = check_git(&mut report);
+    if git_ok {
+        check_repository(&mut report);
+    }
+    if let Some(provider) = check_config(&mut report, args).await {
+        check_provider(&mut report, &provider).await;
+    }
+    if git_ok {
+        check_hooks(&mut report);
+    }
+
+    println!();
+    println!(
+        "Summary: {} passed, {} warnings, {} failed",
+        report.passed, report.warnings, report.failed
+    );
+
+    if report.failed > 0 {
+        return Err(format!("{} check(s) failed", report.failed).into());
+    }
+    Ok(())
+}
+
+fn check_git(report: &mut Report) -> bool {
+    match git::git(&["--version"]) {
+        Ok(version) => {
+            report.check(Status::Pass, "git", version.trim());
+            true
+        },
+        Err(e) => {
+            report.check(Status::Fail, "git", &format!("git is not available: {}", e));
+            false
+        },
+    }
+}
+
+fn check_repository(report: &mut Report) {
+    let root = match git::repo_root() {
+        Some(root) => root,
+        None => {
+            report.check(Status::Fail, "repository", "Not inside a git repository");
+            return;
+        },
+    };
+
+    let branch = git::current_branch().unwrap_or_else(|| "detached HEAD".to_string());
+    report.check(Status::Pass, "repository", &format!("{} (on {})", root.display(), branch));
+
+    match git::git(&["status", "--porcelain"]) {
+        Ok(status) => {
+            let (mut staged, mut unstaged, mut untracked) = (0, 0, 0);
+            for line in status.lines() {
+                let mut chars = line.chars();
+                let index = chars.next().unwrap_or(' ');
+                let worktree = chars.next().unwrap_or(' ');
+                if index == '?' {
+                    untracked += 1;
+                    continue;
+                }
+                if index != ' ' {
+                    staged += 1;
+                }
+                if worktree != ' ' {
+                    unstaged += 1;
+                }
+            }
+            let detail = format!("{} staged, {} unstaged, {} untracked", staged, unstaged, untracked);
+            if staged + unstaged + untracked == 0 {
+                report.check(Status::Info, "changes", &format!("{} (nothing to commit)", detail));
+            } else {
+                report.check(Status::Pass, "changes", &detail);
+            }
+        },
+        Err(e) => report.check(Status::Fail, "changes", &e.to_string()),
+    }
+
+    // An unfinished operation changes what a commit means
+    let in_progress = [
+        ("MERGE_HEAD", "merge"),
+        ("REVERT_HEAD", "revert"),
+        ("CHERRY_PICK_HEAD", "cherry-pick"),
+        ("rebase-merge", "rebase"),
+        ("rebase-apply", "rebase"),
+    ];
+    for (file, operation) in in_progress {
+        if git::git_path(file).is_some_and(|path| path.exists()) {
+            report.check(Status::Warn, "operation", &format!("A {} is in progress", operation));
+            break;
+        }
+    }
+}
+
+async fn check_config(report: &mut Report, args: &[String]) -> Option<ModelProvider> {
+    let (config_path, selected_provider) = match crate::config_args(args) {
+        Ok(values) => values,
+        Err(e) => {
+            report.check(Status::Fail, "config", &e.to_string());
+            return None;
+        },
+    };
+
+    let file = match config::config_file(config_path.as_deref()) {
+        Ok(file) => file,
+        Err(e) => {
+            report.check(Status::Fail, "config", &e.to_string());
+            return None;
+        },
+    };
+
+    let Some(file) = file else {
+        report.check(Status::Info, "config", "No config file found, using command-line flags and defaults");
+        return match crate::parse_args(args).await {
+            Ok(settings) => Some(settings.provider),
+            Err(e) => {
+                report.check(Status::Fail, "provider", &e.to_string());
+                None
+            },
+        };
+    };
+    report.check(Status::Pass, "config file", &file.display().to_string());
+
+    let config: Config = match config::load_config(&file) {
+        Ok(config) => config,
+        Err(e) => {
+            report.check(Status::Fail, "config", &e.to_string());
+            return None;
+        },
+    };
+
+    match config::config_to_provider(&config, selected_provider.as_deref()) {
+        Ok((provider, _)) => Some(provider),
+        Err(e) => {
+            report.check(Status::Fail, "provider", &e.to_string());
+            None
+        },
+    }
+}
+
+async fn check_provider(report: &mut Report, provider: &ModelProvider) {
+
+    let target = match provider {
+        ModelProvider::Gemini { .. } => "gemini".to_string(),
+        ModelProvider::Ollama { base_url, .. } => format!("ollama at {}", base_url),
+        ModelProvider::OpenAI { base_url, .. } => format!("openai at {}", base_url),
+    };
+
+    let models = match providers::list_models(provider).await {
+        Ok(models) => models,
+        Err(e) => {
+            let detail = match e.downcast_ref::<ApiError>() {
+                // Gemini answers an invalid key with 400 rather than 401
+                Some(api) if api.status.as_u16() == 401 || api.status.as_u16() == 403
+                    || (provider.name() == "gemini" && api.status.as_u16() == 400) => {
+                    format!("{}: authentication failed ({})", target, api.status)
+                },
+                Some(api) => format!("{}: {}", target, api),
+                None => format!("{} is unreachable: {}", target, e),
+            };
+            report.check(Status::Fail, "provider", &detail);
+            return;
+        },
+    };
+
+    let auth = if provider.api_key().is_some() { " and accepted the API key" } else { "" };
+    report.check(Status::Pass, "provider", &format!("{} is reachable{}", target, auth));
+
+    if models.iter().any(|m| m == provider.model()) {
+        report.check(Status::Pass, "model", &format!("{} is available", provider.model()));
+    } else if models.is_empty() {
+        report.check(Status::Warn, "model", &format!("{}: the provider reported no models", provider.model()));
+    } else {
+        let shown: Vec<&str> = models.iter().take(5).map(String::as_str).collect();
+        report.check(
+            Status::Fail,
+            "model",
+            &format!("{} not found (available: {}{})", provider.model(), shown.join(", "),
+                if models.len() > shown.len() { ", ..." } else { "" }),
+        );
+    }
+}
+
+fn check_hooks(report: &mut Report) {
+    let hooks_dir = match git::git_path("hooks") {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    for hook in ["prepare-commit-msg", "commit-msg"] {
+        let path = hooks_dir.join(hook);
+        match fs::read_to_string(&path) {
+            Ok(content) if content.contains("acommit") => {
+                report.check(Status::Pass, "hook", &format!("{} is installed ({})", hook, path.display()));
+            },
+            Ok(_) => {
+                report.check(Status::Info, "hook", &format!("{} exists but does not run acommit", hook));
+            },
+            Err(_) => {
+                report.check(Status::Info, "hook", &format!("{} is not installed", hook));
+            },
+        }
+    }
+}
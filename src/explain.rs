@@ -0,0 +1,65 @@
+//! `acommit explain <rev>`: a plain-language explanation of what a commit
+//! (or a range of commits) changed and why it might matter.
+
+use crate::conventional;
+use crate::git;
+use crate::prompt::{self, Changes};
+use crate::providers::{self, Prompt};
+
+const USAGE: &str = "Usage: acommit explain <rev> (a commit, e.g. HEAD~2, or a range like v1.2.0..v1.3.0)";
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let rev = args.get(2).filter(|a| !a.starts_with("--")).ok_or(USAGE)?;
+
+    let settings = crate::parse_args(args).await?;
+
+    let (what, mut changes) = match git::range_ends(rev)? {
+        Some((from, to)) => {
+            let mut changes = Changes::between(&from, &to)?;
+            changes.commits = git::commit_messages(&format!("{}..{}", from, to))?;
+            if changes.commits.is_empty() {
+                return Err(format!("No commits in {}", rev).into());
+            }
+            (format!("these {} commits", changes.commits.len()), changes)
+        },
+        None => {
+            let commit = git::git(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])
+                .map_err(|_| format!("{} is not a commit", rev))?
+                .trim()
+                .to_string();
+            // A merge is explained by what it brought into its first parent
+            let parent = match git::git(&["rev-parse", "--verify", "-q", &format!("{}^1", commit)]) {
+                Ok(parent) => parent.trim().to_string(),
+                Err(_) => git::empty_tree()?,
+            };
+            let mut changes = Changes::between(&parent, &commit)?;
+            changes.commits = vec![git::git(&["log", "-1", "--format=%B", &commit])?];
+            ("this commit".to_string(), changes)
+        },
+    };
+    if changes.is_empty() {
+        eprintln!("⚠️  {} changed no files", rev);
+        changes.diff = "(no changes)".to_string();
+    }
+
+    eprintln!("🤖 Explaining {} with AI...", rev);
+    let prompt = explain_prompt(&settings.config, &what, &changes);
+    let answer = providers::generate(&settings.provider, &prompt).await?;
+    println!("{}", conventional::strip_think_blocks(&answer).trim());
+    Ok(())
+}
+
+fn explain_prompt(config: &crate::config::Config, what: &str, changes: &Changes) -> Prompt {
+    let messages: Vec<String> = changes.commits.iter().map(|m| prompt::commit_item(m)).collect();
+    Prompt::user(format!(
+        "Explain {} in plain {} to a developer who doesn't know this code.\n\n\
+        Commit messages:\n{}\n\nChanged files:\n{}\n\nDiff:\n{}\n\n\
+        Structure the explanation as:\n\
+        - A short paragraph on what changed and the likely reason for it\n\
+        - The notable changes as a bullet list\n\
+        - Why it might matter: behaviour changes, risks, what to look out for\n\
+        Take the intent from the commit messages, but go by the diff and say so if the two disagree. \
+        Don't restate the diff line by line.",
+        what, prompt::language(config), messages.join("\n"), changes.files.trim(), prompt::truncate_diff(&changes.diff)
+    ))
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// A git command that printed its own output exited unsuccessfully.
+#[derive(Debug)]
+pub struct GitFailed {
+    pub command: &'static str,
+    /// Git's exit status, which acommit exits with too.
+    pub code: i32,
+}
+
+impl fmt::Display for GitFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "git {} failed with exit status {} (see git's output above)", self.command, self.code)
+    }
+}
+
+impl std::error::Error for GitFailed {}
+
+/// Runs git and returns its stdout, or an error carrying git's stderr.
+pub fn git(args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Like `git`, with `input` written to git's stdin.
+pub fn git_with_input(args: &[&str], input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut child = Command::new("git")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Root of the git repository containing the current directory, if any.
+pub fn repo_root() -> Option<PathBuf> {
+    let root = git(&["rev-parse", "--show-toplevel"]).ok()?;
+    let root = root.trim();
+    if root.is_empty() { None } else { Some(PathBuf::from(root)) }
+}
+
+/// Path of a file inside `.git`, e.g. `MERGE_HEAD` or `hooks` (honours
+/// worktrees and `core.hooksPath`).
+pub fn git_path(name: &str) -> Option<PathBuf> {
+    let path = git(&["rev-parse", "--git-path", name]).ok()?;
+    let path = path.trim();
+    if path.is_empty() { None } else { Some(PathBuf::from(path)) }
+}
+
+/// The first parent of `HEAD`, or the empty tree when `HEAD` is a root
+/// commit, for diffing what the last commit changed.
+pub fn head_parent() -> Result<String, Box<dyn std::error::Error>> {
+    if git(&["rev-parse", "--verify", "-q", "HEAD~1"]).is_ok() {
+        return Ok("HEAD~1".to_string());
+    }
+    empty_tree()
+}
+
+/// The empty tree, which the root commit's changes are diffed against.
+pub fn empty_tree() -> Result<String, Box<dyn std::error::Error>> {
+    Ok(git_with_input(&["hash-object", "-t", "tree", "--stdin"], "")?.trim().to_string())
+}
+
+/// Creates a commit object from `tree` without touching `HEAD`, the index or
+/// the working tree. `env` carries the original author's identity and date.
+pub fn commit_tree(tree: &str, parents: &[String], message: &str, env: &[(&str, String)]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut command = Command::new("git");
+    command.args(["commit-tree", tree]);
+    for parent in parents {
+        command.args(["-p", parent]);
+    }
+    let mut child = command
+        .args(["-F", "-"])
+        .envs(env.iter().map(|(key, value)| (*key, value.as_str())))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git commit-tree failed: {}", stderr.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The abbreviated form of `commit` used in messages.
+pub fn short(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+/// Short name of the checked-out branch, or `None` on a detached HEAD.
+pub fn current_branch() -> Option<String> {
+    let branch = git(&["symbolic-ref", "--short", "-q", "HEAD"]).ok()?;
+    let branch = branch.trim();
+    if branch.is_empty() { None } else { Some(branch.to_string()) }
+}
+
+/// The branch work gets merged into: the remote's default branch if known,
+/// otherwise a local `main` or `master`.
+pub fn default_branch() -> Option<String> {
+    if let Ok(head) = git(&["symbolic-ref", "--short", "-q", "refs/remotes/origin/HEAD"]) {
+        let head = head.trim();
+        if !head.is_empty() {
+            return Some(head.to_string());
+        }
+    }
+    ["main", "master"]
+        .into_iter()
+        .find(|branch| git(&["rev-parse", "--verify", "-q", &format!("refs/heads/{}", branch)]).is_ok())
+        .map(String::from)
+}
+
+/// The ends of `from..to` or `from...to`, with `HEAD` for a missing one, or
+/// `None` when `range` is a single revision. With three dots `from` is the
+/// merge-base, as `git diff from...to` reads it.
+pub fn range_ends(range: &str) -> Result<Option<(String, String)>, Box<dyn std::error::Error>> {
+    let (from, to, symmetric) = match range.split_once("...") {
+        Some((from, to)) => (from, to, true),
+        None => match range.split_once("..") {
+            Some((from, to)) => (from, to, false),
+            None => return Ok(None),
+        },
+    };
+    let from = if from.is_empty() { "HEAD" } else { from };
+    let to = if to.is_empty() { "HEAD" } else { to };
+    if !symmetric {
+        return Ok(Some((from.to_string(), to.to_string())));
+    }
+    let base = git(&["merge-base", from, to])
+        .map_err(|_| format!("{} and {} have no common ancestor", from, to))?;
+    Ok(Some((base.trim().to_string(), to.to_string())))
+}
+
+/// Full messages of the non-merge commits in `range`, oldest first.
+pub fn commit_messages(range: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let log = git(&["log", "--reverse", "--no-merges", "--format=%B%x00", range])?;
+    Ok(log
+        .split('\0')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Runs `git commit -m <message>` with `options`, letting git (and its hooks)
+/// print to the terminal.
+pub fn commit(message: &str, options: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let status = Command::new("git")
+        .args(["commit", "-m", message])
+        .args(options)
+        .status()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !status.success() {
+        return Err(GitFailed { command: "commit", code: status.code().unwrap_or(1) }.into());
+    }
+    Ok(())
+}
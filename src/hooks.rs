@@ -0,0 +1,265 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use dialoguer::console::Term;
+use dialoguer::Select;
+use crate::config::Conventions;
+use crate::git;
+use crate::lint::{self, Lint};
+use crate::prompt;
+use crate::providers::Prompt;
+use crate::redact;
+use crate::ticket;
+use crate::trailers;
+
+// Every hook acommit writes carries this line, so we never touch hooks we didn't install
+const MARKER: &str = "# Installed by acommit";
+
+const HOOKS: [&str; 2] = ["prepare-commit-msg", "commit-msg"];
+
+/// `acommit hook ...`: installs and removes git hooks, and is what the
+/// installed hooks call back into.
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let rest = args.get(3..).unwrap_or_default();
+    match args.get(2).map(String::as_str) {
+        Some("install") => install(rest),
+        Some("uninstall") => uninstall(rest),
+        Some("prepare-commit-msg") => prepare_commit_msg(args, rest).await,
+        Some("commit-msg") => commit_msg(args, rest).await,
+        _ => Err("Usage: acommit hook <install|uninstall> [prepare-commit-msg|commit-msg] [--force]".into()),
+    }
+}
+
+/// Hook named in the arguments, `prepare-commit-msg` by default.
+fn selected_hook(args: &[String]) -> Result<&'static str, Box<dyn std::error::Error>> {
+    match args.iter().find(|a| !a.starts_with("--")) {
+        None => Ok(HOOKS[0]),
+        Some(name) => HOOKS
+            .iter()
+            .copied()
+            .find(|hook| hook == name)
+            .ok_or_else(|| format!("Unknown hook: {} (expected {})", name, HOOKS.join(" or ")).into()),
+    }
+}
+
+fn hook_path(hook: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let hooks_dir = git::git_path("hooks").ok_or("Not a git repository")?;
+    Ok(hooks_dir.join(hook))
+}
+
+fn hook_script(hook: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // Absolute path, because git may run hooks with a PATH that doesn't include acommit
+    let exe = env::current_exe()?;
+    Ok(format!(
+        "#!/bin/sh\n{}\nexec \"{}\" hook {} \"$@\"\n",
+        MARKER,
+        exe.display(),
+        hook
+    ))
+}
+
+fn install(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let force = args.iter().any(|a| a == "--force");
+    let hook = selected_hook(args)?;
+    let path = hook_path(hook)?;
+
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if !existing.contains(MARKER) && !force {
+            return Err(format!(
+                "{} already exists and was not installed by acommit (use --force to replace it)",
+                path.display()
+            ).into());
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, hook_script(hook)?)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!("✅ Installed {} hook at {}", hook, path.display());
+    if hook == "commit-msg" {
+        println!("Commit messages will now be checked against your conventions");
+    } else {
+        println!("Plain `git commit` will now start with a generated message");
+    }
+    Ok(())
+}
+
+fn uninstall(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let hook = selected_hook(args)?;
+    let path = hook_path(hook)?;
+
+    match fs::read_to_string(&path) {
+        Ok(existing) if existing.contains(MARKER) => {
+            fs::remove_file(&path)?;
+            println!("✅ Removed {} hook", hook);
+            Ok(())
+        },
+        Ok(_) => Err(format!("{} was not installed by acommit, leaving it alone", path.display()).into()),
+        Err(_) => {
+            println!("✅ No {} hook installed", hook);
+            Ok(())
+        },
+    }
+}
+
+/// Called by git as `prepare-commit-msg <file> [<source> [<sha>]]`.
+/// Never fails the commit: problems are reported and the message is left as is.
+async fn prepare_commit_msg(args: &[String], hook_args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let message_file = match hook_args.first() {
+        Some(file) => PathBuf::from(file),
+        None => return Err("prepare-commit-msg expects the commit message file".into()),
+    };
+
+    // message: -m/-F, merge: merge commit, squash: --squash, commit: -c/-C/--amend.
+    // Only an empty source or a commit template gets a generated message.
+    match hook_args.get(1).map(String::as_str) {
+        None | Some("") | Some("template") => {}
+        Some(_) => return Ok(()),
+    }
+
+    if let Err(e) = fill_message(args, &message_file).await {
+        eprintln!("⚠️  acommit: could not generate a commit message: {}", redact::redact(&e.to_string()));
+    }
+    Ok(())
+}
+
+async fn fill_message(args: &[String], message_file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Only the program name: the hook's own arguments aren't acommit options
+    let settings = crate::parse_args(&args[..1]).await?;
+    let provider = &settings.provider;
+
+    let changes = prompt::Changes::collect(false)?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("🤖 acommit: generating commit message with {}...", provider.model());
+    let ticket = ticket::from_branch(&settings.config)?;
+    let trailers = trailers::collect(&args[..1], &settings.config, ticket.as_deref())?;
+    let prompt = prompt::commit_prompt(&settings.config, &changes, ticket.as_deref())?;
+    let prefix_ticket = ticket::prefix(&settings.config, ticket.as_deref());
+    let commit_message = crate::generate_commit_message(provider, &prompt, &settings.config.conventions(), prefix_ticket).await?;
+    let commit_message = trailers::append(&commit_message, &trailers)?;
+
+    // Keep whatever git put in the file (template, status comments) below the suggestion
+    let existing = fs::read_to_string(message_file).unwrap_or_default();
+    fs::write(message_file, format!("{}\n{}", commit_message, existing))?;
+    Ok(())
+}
+
+enum Decision {
+    Use(String),
+    Keep,
+    Abort,
+}
+
+/// Called by git as `commit-msg <file>`. Rejects messages that break the
+/// configured conventions, offering an AI-corrected version when a terminal
+/// is available.
+async fn commit_msg(args: &[String], hook_args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let message_file = match hook_args.first() {
+        Some(file) => PathBuf::from(file),
+        None => return Err("commit-msg expects the commit message file".into()),
+    };
+
+    let message = lint::strip_comments(&fs::read_to_string(&message_file)?);
+    if message.is_empty() || lint::is_exempt(&message) {
+        return Ok(());
+    }
+
+    let conventions = crate::load_config_from_args(&args[..1])?
+        .map(|config| config.conventions())
+        .unwrap_or_default();
+    let problems = lint::lint(&message, &conventions);
+    if problems.is_clean() {
+        return Ok(());
+    }
+
+    eprintln!("❌ acommit: the commit message does not follow the conventions:");
+    for problem in problems.all() {
+        eprintln!("  - {}", problem);
+    }
+
+    // Git runs hooks without stdin, but the terminal is usually still there
+    if Term::stderr().is_term() {
+        match offer_fix(args, &message, &problems, &conventions).await? {
+            Decision::Use(fixed) => {
+                fs::write(&message_file, fixed + "\n")?;
+                return Ok(());
+            },
+            Decision::Keep => return Ok(()),
+            Decision::Abort => {},
+        }
+    }
+
+    Err("Commit message rejected (edit it, or bypass the check with git commit --no-verify)".into())
+}
+
+async fn offer_fix(args: &[String], message: &str, problems: &Lint, conventions: &Conventions) -> Result<Decision, Box<dyn std::error::Error>> {
+    let choices = ["Ask AI for a corrected version", "Commit my message as is", "Abort the commit"];
+    match Select::new().with_prompt("What now?").items(&choices).default(0).interact()? {
+        0 => {},
+        1 => return Ok(Decision::Keep),
+        _ => return Ok(Decision::Abort),
+    }
+
+    let fixed = match correct_message(args, message, problems, conventions).await {
+        Ok(fixed) => fixed,
+        Err(e) => {
+            eprintln!("⚠️  Could not get a correction: {}", redact::redact(&e.to_string()));
+            return Ok(Decision::Abort);
+        },
+    };
+
+    eprintln!("📋 Suggested message:\n\n{}\n", fixed);
+    let remaining = lint::lint(&fixed, conventions);
+    for problem in remaining.all() {
+        eprintln!("  ⚠️  {}", problem);
+    }
+
+    let choices = ["Use the suggested message", "Commit my message as is", "Abort the commit"];
+    Ok(match Select::new().with_prompt("Which message?").items(&choices).default(0).interact()? {
+        0 => Decision::Use(fixed),
+        1 => Decision::Keep,
+        _ => Decision::Abort,
+    })
+}
+
+/// Subject problems go to the model, which only returns a single line;
+/// the body is re-wrapped locally so its text is never rewritten.
+async fn correct_message(args: &[String], message: &str, problems: &Lint, conventions: &Conventions) -> Result<String, Box<dyn std::error::Error>> {
+    let mut lines = message.lines();
+    let mut subject = lines.next().unwrap_or("").to_string();
+    let body: Vec<&str> = lines.skip_while(|line| line.trim().is_empty()).collect();
+
+    if !problems.subject.is_empty() {
+        let provider = crate::parse_args(&args[..1]).await?.provider;
+        let problem_list: Vec<String> = problems.subject.iter().map(|p| format!("- {}", p)).collect();
+        // A ticket reference the author wrote is kept as it was
+        let ticket = lint::split_ticket(&subject).0.map(str::to_string);
+        let prompt = Prompt::user(format!(
+            "Rewrite the first line of this git commit message so it follows the rules. Keep its meaning and don't invent changes.\n\nCommit message:\n{}\n\nProblems:\n{}\n\nRules:\n{}\n- Only return the corrected first line, nothing else",
+            message,
+            problem_list.join("\n"),
+            prompt::convention_rules(conventions, ticket.as_deref())
+        ));
+        eprintln!("🤖 Asking {} for a corrected subject...", provider.model());
+        let corrected = crate::generate_commit_message(&provider, &prompt, conventions, ticket.as_deref()).await?;
+        subject = corrected.lines().next().unwrap_or_default().to_string();
+    }
+
+    let fixed = if body.is_empty() {
+        subject
+    } else {
+        format!("{}\n\n{}", subject, body.join("\n"))
+    };
+    Ok(if problems.body.is_empty() { fixed } else { lint::rewrap_body(&fixed, conventions.body_wrap) })
+}
@@ -0,0 +1,142 @@
+use serde_json::Value;
+use std::env;
+
+/// Expands `${VAR}` and `${VAR:-default}` in every string of a parsed config.
+/// `$$` produces a literal `$`.
+pub fn interpolate_value(value: &mut Value) -> Result<(), String> {
+    match value {
+        Value::String(s) => {
+            *s = interpolate_str(s)?;
+        }
+        Value::Array(items) => {
+            for item in items {
+                interpolate_value(item)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                interpolate_value(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn interpolate_str(input: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        output.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        if let Some(stripped) = after.strip_prefix('$') {
+            output.push('$');
+            rest = stripped;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("Unterminated '${{' in config value: {}", input))?;
+            output.push_str(&expand(&body[..end])?);
+            rest = &body[end + 1..];
+        } else {
+            output.push('$');
+            rest = after;
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn expand(expr: &str) -> Result<String, String> {
+    let (name, default) = match expr.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (expr, None),
+    };
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Invalid variable name in config: ${{{}}}", expr));
+    }
+
+    // Like the shell, `:-` also falls back when the variable is set but empty
+    match (env::var(name).ok().filter(|v| !v.is_empty()), default) {
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.to_string()),
+        (None, None) => match env::var(name) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(format!(
+                "Environment variable {} is not set (referenced in config as ${{{}}})",
+                name, expr
+            )),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Each test uses its own variables, since tests run in parallel
+    #[test]
+    fn expands_set_variables() {
+        env::set_var("ACOMMIT_TEST_SET", "secret");
+        assert_eq!(interpolate_str("key-${ACOMMIT_TEST_SET}").unwrap(), "key-secret");
+    }
+
+    #[test]
+    fn uses_default_when_unset_or_empty() {
+        env::remove_var("ACOMMIT_TEST_UNSET");
+        env::set_var("ACOMMIT_TEST_EMPTY", "");
+        assert_eq!(interpolate_str("${ACOMMIT_TEST_UNSET:-http://localhost:11434}").unwrap(), "http://localhost:11434");
+        assert_eq!(interpolate_str("${ACOMMIT_TEST_EMPTY:-fallback}").unwrap(), "fallback");
+        assert_eq!(interpolate_str("${ACOMMIT_TEST_UNSET:-}").unwrap(), "");
+    }
+
+    #[test]
+    fn default_is_ignored_when_set() {
+        env::set_var("ACOMMIT_TEST_MODEL", "llama3");
+        assert_eq!(interpolate_str("${ACOMMIT_TEST_MODEL:-other}").unwrap(), "llama3");
+    }
+
+    #[test]
+    fn double_dollar_is_a_literal_dollar() {
+        assert_eq!(interpolate_str("cost $$5").unwrap(), "cost $5");
+        assert_eq!(interpolate_str("$${NOT_EXPANDED}").unwrap(), "${NOT_EXPANDED}");
+    }
+
+    #[test]
+    fn lone_dollar_is_kept() {
+        assert_eq!(interpolate_str("$HOME and $").unwrap(), "$HOME and $");
+    }
+
+    #[test]
+    fn missing_variable_is_an_error() {
+        env::remove_var("ACOMMIT_TEST_MISSING");
+        let error = interpolate_str("${ACOMMIT_TEST_MISSING}").unwrap_err();
+        assert!(error.contains("ACOMMIT_TEST_MISSING is not set"), "{}", error);
+    }
+
+    #[test]
+    fn set_but_empty_variable_without_default_is_empty() {
+        env::set_var("ACOMMIT_TEST_BLANK", "");
+        assert_eq!(interpolate_str("[${ACOMMIT_TEST_BLANK}]").unwrap(), "[]");
+    }
+
+    #[test]
+    fn malformed_references_are_errors() {
+        assert!(interpolate_str("${UNTERMINATED").unwrap_err().contains("Unterminated"));
+        assert!(interpolate_str("${}").unwrap_err().contains("Invalid variable name"));
+        assert!(interpolate_str("${BAD-NAME}").unwrap_err().contains("Invalid variable name"));
+    }
+
+    #[test]
+    fn walks_nested_values() {
+        env::set_var("ACOMMIT_TEST_NESTED", "k");
+        let mut value = json!({ "gemini": { "api_key": "${ACOMMIT_TEST_NESTED}" }, "list": ["$${x}", 3], "flag": true });
+        interpolate_value(&mut value).unwrap();
+        assert_eq!(value, json!({ "gemini": { "api_key": "k" }, "list": ["${x}", 3], "flag": true }));
+    }
+}
@@ -0,0 +1,468 @@
+use crate::config::{Casing, Conventions};
+use crate::conventional;
+
+// Common verbs whose inflected forms ("added", "fixes", "updating") give away
+// a non-imperative subject
+const VERBS: &[&str] = &[
+    "add", "adjust", "allow", "apply", "avoid", "bump", "change", "clean",
+    "configure", "convert", "copy", "correct", "create", "delete", "deprecate",
+    "disable", "document", "drop", "enable", "ensure", "extract", "fix",
+    "handle", "hide", "implement", "improve", "initialize", "install",
+    "introduce", "load", "merge", "migrate", "modify", "move", "optimize",
+    "parse", "prevent", "refactor", "release", "remove", "rename", "replace",
+    "restore", "return", "revert", "rewrite", "save", "show", "simplify",
+    "split", "support", "test", "tweak", "update", "upgrade", "use",
+    "validate", "wrap",
+];
+
+const IRREGULAR: &[(&str, &str)] = &[
+    ("built", "build"), ("dropped", "drop"), ("dropping", "drop"),
+    ("got", "get"), ("made", "make"), ("ran", "run"), ("rewrote", "rewrite"),
+    ("splitting", "split"), ("wrapped", "wrap"), ("wrapping", "wrap"),
+    ("wrote", "write"),
+];
+
+/// Problems found in a commit message, split by the part they concern.
+#[derive(Default)]
+pub struct Lint {
+    pub subject: Vec<String>,
+    pub body: Vec<String>,
+}
+
+impl Lint {
+    pub fn is_clean(&self) -> bool {
+        self.subject.is_empty() && self.body.is_empty()
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &String> {
+        self.subject.iter().chain(self.body.iter())
+    }
+}
+
+/// Removes what git strips before committing: comment lines and everything
+/// below the `--verbose` scissors line.
+pub fn strip_comments(message: &str) -> String {
+    let mut lines = Vec::new();
+    for line in message.lines() {
+        if line.starts_with("# ") && line.contains(">8") {
+            break;
+        }
+        if !line.starts_with('#') {
+            lines.push(line.trim_end());
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Messages git writes itself, or that git's autosquash relies on.
+pub fn is_exempt(message: &str) -> bool {
+    let subject = message.lines().next().unwrap_or("");
+    ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+}
+
+pub fn lint(message: &str, conventions: &Conventions) -> Lint {
+    let mut lint = Lint::default();
+    let subject = message.lines().next().unwrap_or("").trim_end();
+
+    let length = subject.chars().count();
+    if length > conventions.max_subject_length {
+        lint.subject.push(format!(
+            "Subject is {} characters long (max {})",
+            length, conventions.max_subject_length
+        ));
+    }
+
+    let (ticket, rest) = split_ticket(subject);
+    if conventions.ticket_prefix && ticket.is_none() {
+        lint.subject.push("Subject must start with a ticket reference like [PROJ-123]".to_string());
+    }
+    let (emoji, header) = split_emoji(rest, conventions);
+
+    // The message as it reads without the ticket and emoji
+    let core: Vec<&str> = std::iter::once(header).chain(message.lines().skip(1)).collect();
+    let core = core.join("\n");
+
+    let description = if conventions.types.is_empty() {
+        if message.lines().nth(1).is_some_and(|line| !line.trim().is_empty()) {
+            lint.body.push("The body must be separated from the subject by a blank line".to_string());
+        }
+        Some(header.to_string())
+    } else {
+        match conventional::parse_header(header) {
+            Ok((commit_type, scope, _, description)) => {
+                if !conventions.types.contains(&commit_type) {
+                    lint.subject.push(format!(
+                        "Type '{}' is not allowed (use one of: {})",
+                        commit_type, conventions.types.join(", ")
+                    ));
+                }
+                let scopes = conventions.allowed_scopes();
+                if let Some(scope) = scope.filter(|s| !scopes.is_empty() && !scopes.contains(s)) {
+                    lint.subject.push(format!(
+                        "Scope '{}' is not allowed (use one of: {})",
+                        scope, scopes.join(", ")
+                    ));
+                }
+                if let Some(expected) = conventions.emoji.get(&commit_type) {
+                    if emoji != Some(expected.as_str()) {
+                        lint.subject.push(format!(
+                            "Start the subject with {} for '{}' commits",
+                            expected, commit_type
+                        ));
+                    }
+                }
+
+                // The rest of the message: blank line after the subject, footers
+                if let Err(e) = conventional::parse(&core) {
+                    lint.body.push(e);
+                }
+                Some(description)
+            },
+            Err(e) => {
+                lint.subject.push(e);
+                None
+            },
+        }
+    };
+
+    if let Some(description) = description {
+        if let Some(problem) = casing_problem(&description, conventions.casing) {
+            lint.subject.push(problem);
+        }
+        if conventions.imperative {
+            let first_word = description.split_whitespace().next().unwrap_or("");
+            if let Some(verb) = imperative_of(first_word) {
+                lint.subject.push(format!(
+                    "Use the imperative mood: '{}' instead of '{}'",
+                    verb, first_word
+                ));
+            }
+        }
+    }
+
+    // Footers such as sign-offs must stay as written, however long
+    let body: Vec<&str> = message.lines().skip(1).collect();
+    let too_long = body[..footer_block_start(&body)]
+        .iter()
+        .filter(|line| is_wrappable(line) && line.chars().count() > conventions.body_wrap)
+        .count();
+    if too_long > 0 {
+        lint.body.push(format!(
+            "{} body line(s) longer than {} characters",
+            too_long, conventions.body_wrap
+        ));
+    }
+
+    lint
+}
+
+/// Splits a leading `[PROJ-123]` ticket reference off a subject.
+pub fn split_ticket(subject: &str) -> (Option<&str>, &str) {
+    let split = subject
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .filter(|(ticket, _)| is_ticket(ticket));
+    match split {
+        Some((ticket, rest)) => (Some(ticket), rest.trim_start()),
+        None => (None, subject),
+    }
+}
+
+/// `PROJ-123`: an uppercase project key, a dash and a number.
+fn is_ticket(text: &str) -> bool {
+    match text.split_once('-') {
+        Some((key, number)) => {
+            key.starts_with(|c: char| c.is_ascii_uppercase())
+                && key.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit())
+        },
+        None => false,
+    }
+}
+
+/// Splits a leading emoji, or a `:shortcode:`, off a subject. A word counts as
+/// an emoji if it is one of `conventions.emoji` or made of emoji code points
+/// only, so that a subject in a non-Latin script keeps its first word.
+pub fn split_emoji<'a>(subject: &'a str, conventions: &Conventions) -> (Option<&'a str>, &'a str) {
+    let (first, rest) = match subject.split_once(' ') {
+        Some(split) => split,
+        None => return (None, subject),
+    };
+    let is_shortcode = first.len() > 2
+        && first.starts_with(':')
+        && first.ends_with(':')
+        && first[1..first.len() - 1].chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    let is_configured = conventions.emoji.values().any(|emoji| emoji == first);
+    let is_emoji = first.chars().any(is_emoji_char)
+        && first.chars().all(|c| is_emoji_char(c) || is_emoji_modifier(c));
+    if is_shortcode || is_configured || is_emoji {
+        (Some(first), rest.trim_start())
+    } else {
+        (None, subject)
+    }
+}
+
+/// Pictographs, dingbats and the other symbol blocks emoji are drawn from.
+fn is_emoji_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A9}' | '\u{00AE}' | '\u{203C}' | '\u{2049}' | '\u{2122}' | '\u{2139}'
+            | '\u{2190}'..='\u{21FF}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{24C2}'
+            | '\u{25A0}'..='\u{27BF}'
+            | '\u{2900}'..='\u{297F}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{3030}' | '\u{303D}' | '\u{3297}' | '\u{3299}'
+            | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
+/// Joiners, variation selectors and keycaps that combine with an emoji.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c, '\u{200D}' | '\u{20E3}' | '\u{FE0E}' | '\u{FE0F}' | '\u{E0020}'..='\u{E007F}')
+}
+
+/// Removes any ticket reference and emoji from the subject of `message`.
+pub fn strip_prefixes(message: &str, conventions: &Conventions) -> String {
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or("");
+    let (_, rest) = split_ticket(subject);
+    let (_, header) = split_emoji(rest, conventions);
+    std::iter::once(header).chain(lines).collect::<Vec<_>>().join("\n")
+}
+
+/// Puts the ticket reference and the emoji for `commit_type` in front of the
+/// subject of `message`.
+pub fn add_prefixes(message: &str, commit_type: Option<&str>, conventions: &Conventions, ticket: Option<&str>) -> String {
+    let mut prefix = String::new();
+    if let Some(ticket) = ticket {
+        prefix.push_str(&format!("[{}] ", ticket));
+    }
+    if let Some(emoji) = commit_type.and_then(|t| conventions.emoji.get(t)) {
+        prefix.push_str(&format!("{} ", emoji));
+    }
+    format!("{}{}", prefix, message)
+}
+
+/// Characters `add_prefixes` may add, which the model has to leave room for.
+pub fn prefix_length(conventions: &Conventions, ticket: Option<&str>) -> usize {
+    let ticket = ticket.map(|t| t.chars().count() + 3).unwrap_or(0);
+    let emoji = conventions.emoji.values().map(|e| e.chars().count() + 1).max().unwrap_or(0);
+    ticket + emoji
+}
+
+// Acronyms such as "API" or "README" keep their case whatever the convention
+fn is_acronym(word: &str) -> bool {
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    letters.len() > 1 && letters.iter().all(|c| c.is_uppercase())
+}
+
+fn casing_problem(description: &str, casing: Casing) -> Option<String> {
+    let first_word = description.split_whitespace().next()?;
+    let first = first_word.chars().next()?;
+    if is_acronym(first_word) {
+        return None;
+    }
+    match casing {
+        Casing::Lower if first.is_uppercase() => Some("Start the description with a lowercase letter".to_string()),
+        Casing::Sentence if first.is_lowercase() => Some("Start the description with a capital letter".to_string()),
+        _ => None,
+    }
+}
+
+/// Changes the first letter of `description` to follow `casing`.
+pub fn apply_casing(description: &str, casing: Casing) -> String {
+    let first_word = description.split_whitespace().next().unwrap_or("");
+    let mut chars = description.chars();
+    let first = match chars.next() {
+        Some(first) if !is_acronym(first_word) => first,
+        _ => return description.to_string(),
+    };
+    match casing {
+        Casing::Lower => first.to_lowercase().chain(chars).collect(),
+        Casing::Sentence => first.to_uppercase().chain(chars).collect(),
+        Casing::Any => description.to_string(),
+    }
+}
+
+/// The imperative form of `word` if it is an inflected common verb.
+fn imperative_of(word: &str) -> Option<String> {
+    let word = word.to_lowercase();
+    if let Some((_, verb)) = IRREGULAR.iter().find(|(form, _)| *form == word) {
+        return Some(verb.to_string());
+    }
+    VERBS
+        .iter()
+        .find(|verb| inflections(verb).contains(&word))
+        .map(|verb| verb.to_string())
+}
+
+fn inflections(verb: &str) -> Vec<String> {
+    if let Some(stem) = verb.strip_suffix('e') {
+        vec![format!("{}s", verb), format!("{}d", verb), format!("{}ing", stem)]
+    } else if let Some(stem) = verb.strip_suffix('y').filter(|s| !s.ends_with(['a', 'e', 'o', 'u'])) {
+        vec![format!("{}ies", stem), format!("{}ied", stem), format!("{}ing", verb)]
+    } else if verb.ends_with(['s', 'x', 'z']) || verb.ends_with("ch") || verb.ends_with("sh") {
+        vec![format!("{}es", verb), format!("{}ed", verb), format!("{}ing", verb)]
+    } else {
+        vec![format!("{}s", verb), format!("{}ed", verb), format!("{}ing", verb)]
+    }
+}
+
+// URLs, indented code and single long tokens can't be wrapped, so they don't count
+fn is_wrappable(line: &str) -> bool {
+    !line.contains("://") && !line.starts_with("    ") && !line.starts_with('\t') && line.trim().contains(' ')
+}
+
+/// Re-wraps the body paragraphs of a message to `width`, keeping the subject,
+/// blank lines and unwrappable lines as they are.
+pub fn rewrap_body(message: &str, width: usize) -> String {
+    let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+    format!("{}\n\n{}", subject, rewrap(body, width)).trim_end().to_string()
+}
+
+/// Index of the first line of the trailing footer block (`Refs: ...`,
+/// `Signed-off-by: ...`, `BREAKING CHANGE: ...`), or `lines.len()` when the
+/// last paragraph isn't one.
+fn footer_block_start(lines: &[&str]) -> usize {
+    let end = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(0, |i| i + 1);
+    let start = lines[..end].iter().rposition(|line| line.trim().is_empty()).map_or(0, |i| i + 1);
+    let block = &lines[start..end];
+    // Footer values may continue on indented lines
+    let is_footers = block.first().is_some_and(|line| conventional::is_footer_line(line))
+        && block.iter().all(|line| conventional::is_footer_line(line) || line.starts_with([' ', '\t']));
+    if is_footers { start } else { lines.len() }
+}
+
+/// Re-wraps paragraphs of text to `width`, keeping blank lines, unwrappable
+/// lines and the trailing footer block as they are.
+pub fn rewrap(text: &str, width: usize) -> String {
+    let mut output = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let lines: Vec<&str> = text.lines().collect();
+    let (lines, footers) = lines.split_at(footer_block_start(&lines));
+
+    for line in lines.iter().copied().skip_while(|line| line.trim().is_empty()) {
+        if line.trim().is_empty() {
+            wrap_paragraph(&mut paragraph, width, &mut output);
+            output.push(String::new());
+        } else if !is_wrappable(line) {
+            wrap_paragraph(&mut paragraph, width, &mut output);
+            output.push(line.to_string());
+        } else {
+            // A list item starts a new paragraph of its own
+            if line.trim_start().starts_with(['-', '*']) {
+                wrap_paragraph(&mut paragraph, width, &mut output);
+            }
+            paragraph.push(line);
+        }
+    }
+    wrap_paragraph(&mut paragraph, width, &mut output);
+
+    if !footers.is_empty() {
+        while output.last().is_some_and(|line| line.is_empty()) {
+            output.pop();
+        }
+        if !output.is_empty() {
+            output.push(String::new());
+        }
+        output.extend(footers.iter().map(|line| line.trim_end().to_string()));
+    }
+
+    output.join("\n").trim_end().to_string()
+}
+
+fn wrap_paragraph(paragraph: &mut Vec<&str>, width: usize, output: &mut Vec<String>) {
+    if paragraph.is_empty() {
+        return;
+    }
+
+    // List items get a hanging indent so continuation lines line up with the text
+    let first = paragraph[0].trim_start();
+    let indent = if first.starts_with(['-', '*']) { "  " } else { "" };
+
+    let mut current = String::new();
+    for word in paragraph.iter().flat_map(|line| line.split_whitespace()) {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            output.push(std::mem::take(&mut current));
+            current.push_str(indent);
+        } else if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.trim().is_empty() {
+        output.push(current);
+    }
+    paragraph.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrap_keeps_trailers_verbatim() {
+        let message = "feat: add export\n\n\
+            This body line is long enough that it has to be wrapped at the configured width.\n\n\
+            Refs: PROJ-1\nSigned-off-by: Alice <a@x.com>\nCo-authored-by: Bob <b@x.com>";
+        assert_eq!(
+            rewrap_body(message, 40),
+            "feat: add export\n\n\
+            This body line is long enough that it\nhas to be wrapped at the configured\nwidth.\n\n\
+            Refs: PROJ-1\nSigned-off-by: Alice <a@x.com>\nCo-authored-by: Bob <b@x.com>"
+        );
+    }
+
+    #[test]
+    fn rewrap_keeps_breaking_change_footer() {
+        let message = "feat!: drop v1\n\nBREAKING CHANGE: the v1 config format is no longer read, convert it first\n  with the migration script";
+        assert_eq!(rewrap_body(message, 40), message);
+    }
+
+    #[test]
+    fn rewrap_wraps_a_last_paragraph_that_is_not_footers() {
+        assert_eq!(
+            rewrap_body("fix: x\n\nThe parser now reads: quoted values and escapes", 30),
+            "fix: x\n\nThe parser now reads: quoted\nvalues and escapes"
+        );
+    }
+
+    #[test]
+    fn long_trailers_are_not_lint_problems() {
+        let conventions = Conventions { body_wrap: 40, ..Default::default() };
+        let message = "feat: add export\n\nCo-authored-by: Someone With A Long Name <someone.with.a.long.name@example.com>";
+        assert!(lint(message, &conventions).body.is_empty());
+    }
+
+    #[test]
+    fn non_latin_subjects_keep_their_first_word() {
+        let conventions = Conventions { types: Vec::new(), ..Default::default() };
+        assert_eq!(strip_prefixes("Добавить экспорт в CSV", &conventions), "Добавить экспорт в CSV");
+        assert_eq!(strip_prefixes("修复 登录 错误", &conventions), "修复 登录 错误");
+        assert_eq!(strip_prefixes("[PROJ-1] 修复 登录 错误", &conventions), "修复 登录 错误");
+        assert!(lint("Добавить экспорт в CSV", &conventions).is_clean());
+        assert!(lint("修复 登录 错误", &conventions).is_clean());
+    }
+
+    #[test]
+    fn emoji_and_shortcodes_are_split_off() {
+        let conventions = Conventions::default();
+        assert_eq!(split_emoji("✨ feat: add export", &conventions), (Some("✨"), "feat: add export"));
+        assert_eq!(split_emoji("⚡️ perf: cache", &conventions), (Some("⚡️"), "perf: cache"));
+        assert_eq!(split_emoji("👩‍💻 chore: x", &conventions), (Some("👩‍💻"), "chore: x"));
+        assert_eq!(split_emoji(":sparkles: feat: x", &conventions), (Some(":sparkles:"), "feat: x"));
+        assert_eq!(split_emoji("feat: add export", &conventions), (None, "feat: add export"));
+    }
+
+    #[test]
+    fn configured_emoji_are_split_off() {
+        let mut conventions = Conventions::default();
+        conventions.emoji.insert("feat".to_string(), "[новое]".to_string());
+        assert_eq!(split_emoji("[новое] feat: x", &conventions), (Some("[новое]"), "feat: x"));
+    }
+}
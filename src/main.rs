@@ -1,129 +1,85 @@
+mod amend;
+mod branch;
+mod bump;
+mod changelog;
+mod config;
+mod conventional;
+mod doctor;
+mod explain;
+mod git;
+mod hooks;
+mod interpolate;
+mod lint;
+mod models;
+mod operation;
+mod pr;
+mod prompt;
+mod providers;
+mod redact;
+mod review;
+mod reword;
+mod setup;
+mod squash;
+mod summary;
+mod ticket;
+mod trailers;
+
 use std::env;
 use std::process::Command;
 use std::io::{self, Write};
-use std::fs;
-use serde::{Deserialize, Serialize};
-use dialoguer::{Select, Input, Confirm};
-
-#[derive(Debug, Clone)]
-enum ModelProvider {
-    Gemini { api_key: String, model: String },
-    Ollama { base_url: String, model: String },
-    OpenAI { base_url: String, api_key: Option<String>, model: String },
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct ProviderConfig {
-    model: String,
-    #[serde(default)]
-    api_key: Option<String>,
-    #[serde(default)]
-    url: Option<String>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct Config {
-    default_provider: String,
-    #[serde(default)]
-    verbose: bool,
-    gemini: ProviderConfig,
-    ollama: ProviderConfig,
-    openai: ProviderConfig,
-}
-
-// Gemini API structures
-#[derive(Serialize)]
-struct GeminiRequest {
-    contents: Vec<GeminiContent>,
-}
-
-#[derive(Serialize)]
-struct GeminiContent {
-    parts: Vec<GeminiPart>,
-}
-
-#[derive(Serialize)]
-struct GeminiPart {
-    text: String,
-}
-
-#[derive(Deserialize)]
-struct GeminiResponse {
-    candidates: Option<Vec<GeminiCandidate>>,
-}
-
-#[derive(Deserialize)]
-struct GeminiCandidate {
-    content: Option<GeminiResponseContent>,
-}
-
-#[derive(Deserialize)]
-struct GeminiResponseContent {
-    parts: Option<Vec<GeminiResponsePart>>,
-}
-
-#[derive(Deserialize)]
-struct GeminiResponsePart {
-    text: Option<String>,
-}
-
-// Ollama API structures
-#[derive(Serialize)]
-struct OllamaRequest {
-    model: String,
-    prompt: String,
-    stream: bool,
-}
-
-#[derive(Deserialize)]
-struct OllamaResponse {
-    response: Option<String>,
-    // done: Option<bool>,
-}
-
-// OpenAI API structures
-#[derive(Serialize)]
-struct OpenAIRequest {
-    model: String,
-    messages: Vec<OpenAIMessage>,
-    max_tokens: Option<u32>,
-    temperature: Option<f32>,
-}
-
-#[derive(Serialize)]
-struct OpenAIMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Deserialize)]
-struct OpenAIResponse {
-    choices: Option<Vec<OpenAIChoice>>,
-}
-
-#[derive(Deserialize)]
-struct OpenAIChoice {
-    message: Option<OpenAIResponseMessage>,
-}
-
-#[derive(Deserialize)]
-struct OpenAIResponseMessage {
-    content: Option<String>,
-}
+use providers::{ModelProvider, Prompt};
 
 #[tokio::main]
 async fn main() {
     if let Err(e) = run().await {
-        eprintln!("❌ Error: {}", e);
-        std::process::exit(1);
+        eprintln!("❌ Error: {}", redact::redact(&e.to_string()));
+        let code = e.downcast_ref::<git::GitFailed>().map(|e| e.code).unwrap_or(1);
+        std::process::exit(code);
     }
 }
 
 async fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let (config, _verbose) = parse_args()?;
+    let mut args: Vec<String> = env::args().collect();
+
+    // Everything after `--` goes to `git commit` untouched
+    let git_tail = match args.iter().position(|a| a == "--") {
+        Some(index) => args.split_off(index).split_off(1),
+        None => Vec::new(),
+    };
+
+    // Subcommands
+    match args.get(1).map(String::as_str) {
+        Some("models") => return models::run(&args).await,
+        Some("doctor") => return doctor::run(&args).await,
+        Some("hook") => return hooks::run(&args).await,
+        Some("prompt") => return prompt::run(&args).await,
+        Some("reword") => return reword::run(&args).await,
+        Some("squash") => return squash::run(&args, &git_tail).await,
+        Some("pr") => return pr::run(&args).await,
+        Some("changelog") => return changelog::run(&args).await,
+        Some("bump") => return bump::run(&args).await,
+        Some("branch") => return branch::run(&args).await,
+        Some("explain") => return explain::run(&args).await,
+        Some("review") => return review::run(&args).await,
+        Some("summary") => return summary::run(&args).await,
+        // A mistyped subcommand would otherwise generate and commit
+        Some(other) if !other.starts_with('-') => {
+            print_usage();
+            return Err(format!("Unknown command: {}", other).into());
+        },
+        _ => {}
+    }
+    if args.iter().any(|a| a == "--amend") {
+        return amend::run(&args, &git_tail).await;
+    }
+
+    let settings = parse_args(&args).await?;
+    let verbose = settings.verbose;
+    let config = &settings.provider;
+    debug_log(verbose, &format!("provider: {:?}", config));
     
     // Debug info
-    match &config {
+    match config {
         ModelProvider::Gemini { model, .. } => println!("🧠 Using Gemini model: {}", model),
         ModelProvider::Ollama { base_url, model } => println!("🦙 Using Ollama model: {} at {}", model, base_url),
         ModelProvider::OpenAI { base_url, model, .. } => println!("🤖 Using OpenAI model: {} at {}", model, base_url),
@@ -133,7 +89,7 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     
     // Check if we're in a git repository
     let status = Command::new("git")
-        .args(&["status", "--porcelain"])
+        .args(["status", "--porcelain"])
         .output()?;
 
     if !status.status.success() {
@@ -151,52 +107,40 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
         println!("  {}", line);
     }
 
-    // Get diff for AI
-    let diff_output = Command::new("git")
-        .args(&["diff", "--cached", "--name-status"])
-        .output()?;
+    // A merge, revert or cherry-pick keeps git's message
+    let operation = operation::detect()?;
+    if let Some(operation) = &operation {
+        println!("🔀 Finishing a {} of {}", operation.kind, git::short(&operation.commit));
+        let unresolved = operation::unresolved(operation)?;
+        if !unresolved.is_empty() {
+            return Err(format!("Resolve the conflicts in {} first", unresolved.join(", ")).into());
+        }
+    }
 
-    let mut diff_info = String::from_utf8_lossy(&diff_output.stdout).to_string();
-    
-    // If there are no staged changes, show all changes
-    if diff_info.trim().is_empty() {
-        let all_diff = Command::new("git")
-            .args(&["diff", "--name-status"])
-            .output()?;
-        diff_info = String::from_utf8_lossy(&all_diff.stdout).to_string();
+    // Staged changes, or all changes if nothing is staged
+    let changes = prompt::Changes::collect(true)?;
+
+    if review::enabled(&args, &settings.config) {
+        review::gate(&settings.provider, &settings.config, &changes).await?;
     }
 
-    println!("🤖 Generating commit message with AI...");
-    
-    // Create prompt for AI
-    let prompt = format!(
-        "Generate a concise, clear git commit message in English based on these file changes:\n\n{}\n\nRules:\n- Use conventional commits format (feat:, fix:, docs:, etc.)\n- Be specific but concise\n- Maximum 50 characters for the title\n- Only return the commit message, nothing else",
-        diff_info.trim()
-    );
-
-    // Call the appropriate API
-    let commit_message = match config {
-        ModelProvider::Gemini { api_key, model } => {
-            call_gemini_api(&api_key, &model, &prompt).await?
-        },
-        ModelProvider::Ollama { base_url, model } => {
-            call_ollama_api(&base_url, &model, &prompt).await?
-        },
-        ModelProvider::OpenAI { base_url, api_key, model } => {
-            call_openai_api(&base_url, api_key.as_ref(), &model, &prompt).await?
+    let commit_options = commit_options(&args, &git_tail)?;
+
+    let commit_message = match &operation {
+        Some(operation) => operation::compose_message(&settings.provider, &settings.config, &args, operation).await?,
+        None => {
+            println!("🤖 Generating commit message with AI...");
+            compose_message(&settings, &args, &changes, &[]).await?
         },
     };
     
     println!("📋 Generated commit message: {}", commit_message);
+    if conventional::parse(&lint::strip_prefixes(&commit_message, &settings.config.conventions())).is_ok_and(|m| m.is_breaking()) {
+        println!("💥 The message marks this as a breaking change");
+    }
     
     // Ask user for confirmation
-    print!("🤔 Use this commit message? (y/N): ");
-    io::stdout().flush()?;
-    
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    
-    if !input.trim().to_lowercase().starts_with('y') {
+    if !confirm("Use this commit message?")? {
         println!("❌ Commit cancelled");
         return Ok(());
     }
@@ -204,7 +148,7 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     // Execute git add -A
     println!("➕ Adding all changes...");
     let add_status = Command::new("git")
-        .args(&["add", "-A"])
+        .args(["add", "-A"])
         .status()?;
 
     if !add_status.success() {
@@ -213,83 +157,122 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
 
     // Execute commit
     println!("💾 Creating commit...");
-    let commit_status = Command::new("git")
-        .args(&["commit", "-m", &commit_message])
-        .status()?;
-
-    if commit_status.success() {
-        println!("✅ Successfully committed with message: {}", commit_message);
-    } else {
-        return Err("Failed to create commit".into());
+    if is_signing(&commit_options) {
+        println!("🔏 The commit will be signed");
     }
+    if let Err(e) = git::commit(&commit_message, &commit_options) {
+        if !commit_options.iter().any(|o| o == "--no-verify") {
+            eprintln!("💡 If a hook rejected the commit, fix what it reports or bypass it with --no-verify");
+        }
+        return Err(e);
+    }
+    println!("✅ Successfully committed with message: {}", commit_message);
 
     Ok(())
 }
 
-fn load_config(config_path: &str) -> Result<Config, Box<dyn std::error::Error>> {
-    let config_content = fs::read_to_string(config_path)?;
-    let config: Config = serde_json::from_str(&config_content)?;
-    Ok(config)
+/// The message offered for `changes`: ticket lookup, prompt, generation and
+/// validation, then trailers. `kept` are trailers of the commits being
+/// rewritten, which go before the new ones.
+async fn compose_message(settings: &Settings, args: &[String], changes: &prompt::Changes, kept: &[trailers::Trailer]) -> Result<String, Box<dyn std::error::Error>> {
+    let conventions = settings.config.conventions();
+    let ticket = ticket::resolve(args, &settings.config)?;
+    let prefix_ticket = ticket::prefix(&settings.config, ticket.as_deref());
+    if let Some(ticket) = &ticket {
+        println!("🎫 Ticket: {}", ticket);
+    }
+    if conventions.ticket_prefix && prefix_ticket.is_none() {
+        println!("⚠️  The conventions ask for a ticket reference, pass it with --ticket <ID> or name the branch after it");
+    }
+    let mut trailers = kept.to_vec();
+    trailers.extend(trailers::collect(args, &settings.config, ticket.as_deref())?);
+    let prompt = prompt::commit_prompt(&settings.config, changes, ticket.as_deref())?;
+
+    let commit_message = generate_commit_message(&settings.provider, &prompt, &conventions, prefix_ticket).await?;
+    trailers::append(&commit_message, &trailers)
 }
 
-fn config_to_provider(config: &Config, provider: Option<&str>) -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
-    let verbose = config.verbose;
-    let selected_provider = provider.unwrap_or(&config.default_provider);
-    
-    match selected_provider {
-        "gemini" => {
-            let api_key = config.gemini.api_key.clone()
-                .or_else(|| env::var("GEMINI_API_KEY").ok())
-                .ok_or("Gemini API key is required")?;
-            Ok((ModelProvider::Gemini {
-                api_key,
-                model: config.gemini.model.clone(),
-            }, verbose))
-        },
-        "ollama" => {
-            let base_url = config.ollama.url.clone()
-                .unwrap_or_else(|| "http://localhost:11434".to_string());
-            Ok((ModelProvider::Ollama {
-                base_url,
-                model: config.ollama.model.clone(),
-            }, verbose))
-        },
-        "openai" => {
-            let base_url = config.openai.url.clone()
-                .ok_or("OpenAI URL is required")?;
-            let api_key = config.openai.api_key.clone()
-                .or_else(|| env::var("OPENAI_API_KEY").ok());
-            Ok((ModelProvider::OpenAI {
-                base_url,
-                api_key,
-                model: config.openai.model.clone(),
-            }, verbose))
-        },
-        _ => Err(format!("Unknown provider: {}", selected_provider).into()),
+// How often the model may answer with something that isn't a valid message
+const MAX_ATTEMPTS: usize = 3;
+
+/// Asks the model for a commit message, cleans up its answer and checks it
+/// against `conventions`, asking again with the problems spelled out when it
+/// doesn't pass. The ticket reference and emoji are added to the result.
+async fn generate_commit_message(provider: &ModelProvider, prompt: &Prompt, conventions: &config::Conventions, ticket: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    let mut attempt_prompt = prompt.clone();
+    let mut problem = String::new();
+
+    // Without a ticket there is nothing to prefix, so don't insist on one
+    let mut rules = conventions.clone();
+    rules.ticket_prefix = conventions.ticket_prefix && ticket.is_some();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let raw = providers::generate(provider, &attempt_prompt).await?;
+        let cleaned = lint::strip_prefixes(&conventional::clean_output(&raw), conventions);
+
+        match tidy_message(&cleaned, conventions) {
+            Ok((message, commit_type)) => {
+                let mut message = lint::add_prefixes(&message, commit_type.as_deref(), conventions, ticket);
+                let problems = lint::lint(&message, &rules);
+                if problems.subject.is_empty() {
+                    // Long body lines are fixable without bothering the model
+                    if !problems.body.is_empty() {
+                        message = lint::rewrap_body(&message, conventions.body_wrap);
+                    }
+                    return Ok(message);
+                }
+                problem = problems.subject.join("; ");
+            },
+            Err(e) => problem = e,
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            eprintln!("⚠️  The model returned an invalid message ({}), asking again...", problem);
+            attempt_prompt.user = format!(
+                "{}\n\nYour previous answer was:\n{}\n\nIt is invalid: {}. Answer again with a corrected commit message only.",
+                prompt.user, cleaned, problem
+            );
+        }
     }
+
+    Err(format!(
+        "The model did not return a valid commit message after {} attempts: {}",
+        MAX_ATTEMPTS, problem
+    ).into())
 }
 
-fn parse_args() -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    
-    // Check for help flags
-    for arg in &args[1..] {
-        if arg == "--help" || arg == "-h" {
-            print_usage();
-            std::process::exit(0);
-        } else if arg == "--example-config" {
-            print_example_config();
-            std::process::exit(0);
-        } else if arg == "--setup" {
-            setup_config()?;
-            std::process::exit(0);
+/// Normalizes a cleaned model answer and fixes its casing. Returns the
+/// message and its commit type (`None` for plain subjects).
+fn tidy_message(message: &str, conventions: &config::Conventions) -> Result<(String, Option<String>), String> {
+    if conventions.types.is_empty() {
+        let mut lines = message.lines();
+        let subject = lines.next().unwrap_or("").split_whitespace().collect::<Vec<_>>().join(" ");
+        let subject = lint::apply_casing(subject.trim_end_matches('.'), conventions.casing);
+        if subject.is_empty() {
+            return Err("Message is empty".to_string());
         }
+        let message: Vec<&str> = std::iter::once(subject.as_str()).chain(lines).collect();
+        return Ok((message.join("\n"), None));
     }
-    
-    // Check for config file and provider selection
+
+    let mut parsed = conventional::parse(message)?;
+    parsed.normalize();
+    parsed.description = lint::apply_casing(&parsed.description, conventions.casing);
+    Ok((parsed.to_string(), Some(parsed.commit_type.clone())))
+}
+
+/// Prints a debug line to stderr with any known secrets masked.
+fn debug_log(verbose: bool, message: &str) {
+    if verbose {
+        eprintln!("Debug - {}", redact::redact(message));
+    }
+}
+
+/// Reads `--config <PATH>` and `--provider <NAME>` from the arguments.
+fn config_args(args: &[String]) -> Result<(Option<String>, Option<String>), Box<dyn std::error::Error>> {
     let mut config_path = None;
     let mut selected_provider = None;
-    
+
     for (i, arg) in args.iter().enumerate().skip(1) {
         if arg == "--config" {
             if let Some(path) = args.get(i + 1) {
@@ -299,24 +282,132 @@ fn parse_args() -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
             }
         } else if arg == "--provider" {
             if let Some(provider) = args.get(i + 1) {
-                selected_provider = Some(provider.as_str());
+                selected_provider = Some(provider.clone());
             } else {
                 return Err("--provider requires a provider name (gemini, ollama, openai)".into());
             }
         }
     }
+
+    Ok((config_path, selected_provider))
+}
+
+/// Reads `--ticket <ID>` (or `--ticket=<ID>`) from the arguments.
+fn ticket_arg(args: &[String]) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    for (i, arg) in args.iter().enumerate().skip(1) {
+        if let Some(ticket) = arg.strip_prefix("--ticket=") {
+            return Ok(Some(ticket.to_string()));
+        } else if arg == "--ticket" {
+            return match args.get(i + 1) {
+                Some(ticket) => Ok(Some(ticket.clone())),
+                None => Err("--ticket requires a ticket ID, e.g. PROJ-123".into()),
+            };
+        }
+    }
+    Ok(None)
+}
+
+/// Reads a subcommand's `<name> <VALUE>` (or `<name>=<VALUE>`) from the arguments.
+fn value_arg(args: &[String], name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    for (i, arg) in args.iter().enumerate().skip(2) {
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Ok(Some(value.to_string()));
+        } else if arg == name {
+            return match args.get(i + 1) {
+                Some(value) => Ok(Some(value.clone())),
+                None => Err(format!("{} requires a value", name).into()),
+            };
+        }
+    }
+    Ok(None)
+}
+
+/// Asks a yes/no `question`; anything but yes is no.
+fn confirm(question: &str) -> io::Result<bool> {
+    print!("🤔 {} (y/N): ", question);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase().starts_with('y'))
+}
+
+/// Options passed through to `git commit`: signing, `--no-verify`,
+/// `--author`, `--date`, and whatever came after `--`.
+fn commit_options(args: &[String], git_tail: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut options = Vec::new();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--no-verify" | "--no-gpg-sign" => options.push(arg.clone()),
+            "--author" | "--date" => {
+                let value = rest.next().ok_or_else(|| format!("{} requires a value", arg))?;
+                options.push(format!("{}={}", arg, value));
+            },
+            _ if arg.starts_with("-S")
+                || arg.starts_with("--gpg-sign")
+                || arg.starts_with("--author=")
+                || arg.starts_with("--date=") => options.push(arg.clone()),
+            _ => {},
+        }
+    }
+    options.extend(git_tail.iter().cloned());
+    Ok(options)
+}
+
+/// Whether `git commit` will sign, by option or by `commit.gpgsign`.
+fn is_signing(options: &[String]) -> bool {
+    let mut signing = git::git(&["config", "--bool", "commit.gpgsign"]).is_ok_and(|v| v.trim() == "true");
+    for option in options {
+        if option.starts_with("-S") || option.starts_with("--gpg-sign") {
+            signing = true;
+        } else if option == "--no-gpg-sign" {
+            signing = false;
+        }
+    }
+    signing
+}
+
+/// The config selected by the arguments, or `None` when no config file
+/// exists.
+fn load_config_from_args(args: &[String]) -> Result<Option<config::Config>, Box<dyn std::error::Error>> {
+    let (config_path, _) = config_args(args)?;
+    match config::config_file(config_path.as_deref())? {
+        Some(path) => Ok(Some(config::load_config(&path)?)),
+        None => Ok(None),
+    }
+}
+
+/// Everything resolved from the command line and config files.
+struct Settings {
+    provider: ModelProvider,
+    verbose: bool,
+    /// The config file's contents, or defaults when there is none.
+    config: config::Config,
+}
+
+async fn parse_args(args: &[String]) -> Result<Settings, Box<dyn std::error::Error>> {
+    // Check for help flags
+    for arg in &args[1..] {
+        if arg == "--help" || arg == "-h" {
+            print_usage();
+            std::process::exit(0);
+        } else if arg == "--example-config" {
+            print_example_config();
+            std::process::exit(0);
+        } else if arg == "--setup" {
+            setup::setup_config().await?;
+            std::process::exit(0);
+        }
+    }
     
-    // Load config from file or environment
-    if let Some(path) = config_path {
-        let config = load_config(&path)?;
-        return config_to_provider(&config, selected_provider);
-    } else if let Ok(path) = env::var("ACOMMIT_CONFIG") {
-        let config = load_config(&path)?;
-        return config_to_provider(&config, selected_provider);
-    } else if fs::metadata("acommit.json").is_ok() {
-        // Auto-detect local acommit.json
-        let config = load_config("acommit.json")?;
-        return config_to_provider(&config, selected_provider);
+    // Check for config file and provider selection
+    let (config_path, selected_provider) = config_args(args)?;
+    
+    // Load the config file (--config, ACOMMIT_CONFIG, acommit.json, then the global one)
+    if let Some(config_file) = config::config_file(config_path.as_deref())? {
+        let config = config::load_config(&config_file)?;
+        let (provider, verbose) = config::config_to_provider(&config, selected_provider.as_deref())?;
+        return Ok(Settings { provider, verbose, config });
     }
     
     let mut gemini_api_key = None;
@@ -326,8 +417,15 @@ fn parse_args() -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
     let mut model_name = None;
     let mut verbose = false;
     
+    // Subcommands read their own options, so only the main flow's are checked
+    let subcommand = args.get(1).is_some_and(|a| !a.starts_with('-'));
+
     // Parse arguments
     for arg in args.iter().skip(1) {
+        // Positional arguments and option values, e.g. `branch "set timeout=30"`
+        if !arg.starts_with('-') {
+            continue;
+        }
         if let Some((key, value)) = arg.split_once('=') {
             match key {
                 "--gemini-key" | "-gk" => gemini_api_key = Some(value.to_string()),
@@ -336,6 +434,10 @@ fn parse_args() -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
                 "--openai-key" | "-ok" => openai_api_key = Some(value.to_string()),
                 "--model" | "-m" => model_name = Some(value.to_string()),
                 "--verbose" => verbose = true,
+                "--ticket" | "--pair" => {}, // read by ticket_arg and trailers::collect
+                "--reason" => {}, // read by operation::reason_arg
+                "--author" | "--date" | "--gpg-sign" => {}, // passed to git commit
+                _ if subcommand => {},
                 _ => return Err(format!("Unknown argument: {}", key).into()),
             }
         } else {
@@ -373,327 +475,95 @@ fn parse_args() -> Result<(ModelProvider, bool), Box<dyn std::error::Error>> {
     }
     
     // Debug output (only if verbose)
-    if verbose {
-        eprintln!("Debug - gemini_api_key: {:?}", gemini_api_key);
-        eprintln!("Debug - ollama_url: {:?}", ollama_url);
-        eprintln!("Debug - openai_url: {:?}", openai_url);
-        eprintln!("Debug - openai_api_key: {:?}", openai_api_key);
-        eprintln!("Debug - model_name: {:?}", model_name);
-    }
+    debug_log(verbose, &format!("gemini_api_key: {}", redact::mask_opt(gemini_api_key.as_deref())));
+    debug_log(verbose, &format!("ollama_url: {:?}", ollama_url));
+    debug_log(verbose, &format!("openai_url: {:?}", openai_url));
+    debug_log(verbose, &format!("openai_api_key: {}", redact::mask_opt(openai_api_key.as_deref())));
+    debug_log(verbose, &format!("model_name: {:?}", model_name));
     
     // Determine provider and configuration
-    if let Some(url) = openai_url {
+    let provider = if let Some(url) = openai_url {
         // OpenAI explicitly specified
         let api_key = openai_api_key
             .or_else(|| env::var("OPENAI_API_KEY").ok());
-        Ok((ModelProvider::OpenAI { 
+        ModelProvider::OpenAI { 
             base_url: url, 
             api_key,
             model: model_name.unwrap_or_else(|| "gpt-3.5-turbo".to_string())
-        }, verbose))
+        }
     } else if let Some(url) = ollama_url {
         // Ollama explicitly specified
-        Ok((ModelProvider::Ollama { 
+        ModelProvider::Ollama { 
             base_url: url, 
             model: model_name.unwrap_or_else(|| "llama3.2:3b".to_string())
-        }, verbose))
+        }
     } else if let Some(key) = gemini_api_key {
         // Gemini key explicitly specified
-        Ok((ModelProvider::Gemini { 
+        ModelProvider::Gemini { 
             api_key: key, 
             model: model_name.unwrap_or_else(|| "gemini-2.5-flash-lite".to_string())
-        }, verbose))
+        }
     } else {
         // No explicit provider, check environment and defaults
         if let Ok(api_key) = env::var("GEMINI_API_KEY") {
-            Ok((ModelProvider::Gemini { 
+            ModelProvider::Gemini { 
                 api_key, 
                 model: model_name.unwrap_or_else(|| "gemini-2.5-flash-lite".to_string())
-            }, verbose))
+            }
         } else {
             // Default to Ollama
-            Ok((ModelProvider::Ollama { 
+            ModelProvider::Ollama { 
                 base_url: "http://localhost:11434".to_string(),
                 model: model_name.unwrap_or_else(|| "llama3.2:3b".to_string())
-            }, verbose))
+            }
         }
-    }
-}
-
-async fn call_gemini_api(api_key: &str, model: &str, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    
-    let request_body = GeminiRequest {
-        contents: vec![GeminiContent {
-            parts: vec![GeminiPart {
-                text: prompt.to_string(),
-            }],
-        }],
-    };
-
-    let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-        model, api_key
-    );
-
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        return Err(format!("Gemini API request failed: {}", response.status()).into());
-    }
-
-    let data: GeminiResponse = response.json().await?;
-    
-    let commit_message = data
-        .candidates
-        .and_then(|candidates| candidates.into_iter().next())
-        .and_then(|candidate| candidate.content)
-        .and_then(|content| content.parts)
-        .and_then(|parts| parts.into_iter().next())
-        .and_then(|part| part.text)
-        .unwrap_or_else(|| "chore: update files".to_string())
-        .trim()
-        .to_string();
-
-    // Remove unnecessary whitespace
-    let clean_message = commit_message
-        .split_whitespace()
-        .collect::<Vec<&str>>()
-        .join(" ");
-
-    Ok(clean_message)
-}
-
-async fn call_ollama_api(base_url: &str, model: &str, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    
-    let request_body = OllamaRequest {
-        model: model.to_string(),
-        prompt: prompt.to_string(),
-        stream: false,
     };
 
-    let url = format!("{}/api/generate", base_url);
-
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        return Err(format!("Ollama API request failed: {}", response.status()).into());
+    if let Some(key) = provider.api_key() {
+        redact::register(key);
     }
-
-    let data: OllamaResponse = response.json().await?;
-    
-    let commit_message = data
-        .response
-        .unwrap_or_else(|| "chore: update files".to_string())
-        .trim()
-        .to_string();
-
-    // Remove unnecessary whitespace and take the first line (sometimes Ollama returns more)
-    let clean_message = commit_message
-        .lines()
-        .next()
-        .unwrap_or("chore: update files")
-        .split_whitespace()
-        .collect::<Vec<&str>>()
-        .join(" ");
-
-    Ok(clean_message)
-}
-
-async fn call_openai_api(base_url: &str, api_key: Option<&String>, model: &str, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    
-    let request_body = OpenAIRequest {
-        model: model.to_string(),
-        messages: vec![OpenAIMessage {
-            role: "user".to_string(),
-            content: prompt.to_string(),
-        }],
-        max_tokens: Some(100),
-        temperature: Some(0.7),
-    };
-
-    let url = format!("{}/chat/completions", base_url);
-
-    let mut request = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&request_body);
-
-    // Add Authorization header only if API key is provided
-    if let Some(key) = api_key {
-        request = request.header("Authorization", format!("Bearer {}", key));
-    }
-
-    let response = request.send().await?;
-
-    if !response.status().is_success() {
-        return Err(format!("OpenAI API request failed: {}", response.status()).into());
-    }
-
-    let data: OpenAIResponse = response.json().await?;
-    
-    let commit_message = data
-        .choices
-        .and_then(|choices| choices.into_iter().next())
-        .and_then(|choice| choice.message)
-        .and_then(|message| message.content)
-        .unwrap_or_else(|| "chore: update files".to_string())
-        .trim()
-        .to_string();
-
-    // Remove unnecessary whitespace and take the first line
-    let clean_message = commit_message
-        .lines()
-        .next()
-        .unwrap_or("chore: update files")
-        .split_whitespace()
-        .collect::<Vec<&str>>()
-        .join(" ");
-
-    Ok(clean_message)
-}
-
-fn setup_config() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🎯 Welcome to acommit setup!");
-    println!();
-    
-    // Select default provider
-    let providers = vec!["Gemini", "Ollama", "OpenAI"];
-    let default_provider_selection = Select::new()
-        .with_prompt("Choose your default AI provider")
-        .items(&providers)
-        .interact()?;
-    
-    let verbose = Confirm::new()
-        .with_prompt("Enable verbose mode by default?")
-        .default(false)
-        .interact()?;
-    
-    // Configure all providers
-    let mut config = Config {
-        default_provider: match default_provider_selection {
-            0 => "gemini".to_string(),
-            1 => "ollama".to_string(),
-            2 => "openai".to_string(),
-            _ => return Err("Invalid provider selection".into()),
-        },
-        verbose,
-        gemini: ProviderConfig {
-            model: "gemini-2.5-flash-lite".to_string(),
-            api_key: None,
-            url: None,
-        },
-        ollama: ProviderConfig {
-            model: "llama3.2:3b".to_string(),
-            api_key: None,
-            url: Some("http://localhost:11434".to_string()),
-        },
-        openai: ProviderConfig {
-            model: "bitnet-model".to_string(),
-            api_key: None,
-            url: Some("http://localhost:7777/v1".to_string()),
-        },
-    };
-    
-    // Configure Gemini
-    println!("\n📝 Configuring Gemini:");
-    let gemini_model: String = Input::new()
-        .with_prompt("Gemini model name")
-        .default("gemini-2.5-flash-lite".to_string())
-        .interact_text()?;
-    config.gemini.model = gemini_model;
-    
-    if Confirm::new()
-        .with_prompt("Do you want to set Gemini API key in config?")
-        .default(false)
-        .interact()? {
-        let api_key: String = Input::new()
-            .with_prompt("Enter Gemini API key")
-            .interact_text()?;
-        config.gemini.api_key = Some(api_key);
-    }
-    
-    // Configure Ollama
-    println!("\n📝 Configuring Ollama:");
-    let ollama_url: String = Input::new()
-        .with_prompt("Ollama URL")
-        .default("http://localhost:11434".to_string())
-        .interact_text()?;
-    config.ollama.url = Some(ollama_url);
-    
-    let ollama_model: String = Input::new()
-        .with_prompt("Ollama model name")
-        .default("llama3.2:3b".to_string())
-        .interact_text()?;
-    config.ollama.model = ollama_model;
-    
-    // Configure OpenAI
-    println!("\n📝 Configuring OpenAI:");
-    let openai_url: String = Input::new()
-        .with_prompt("OpenAI-compatible API URL")
-        .default("http://localhost:7777/v1".to_string())
-        .interact_text()?;
-    config.openai.url = Some(openai_url);
-    
-    let openai_model: String = Input::new()
-        .with_prompt("OpenAI model name")
-        .default("bitnet-model".to_string())
-        .interact_text()?;
-    config.openai.model = openai_model;
-    
-    if Confirm::new()
-        .with_prompt("Do you want to set OpenAI API key in config?")
-        .default(false)
-        .interact()? {
-        let api_key: String = Input::new()
-            .with_prompt("Enter OpenAI API key")
-            .interact_text()?;
-        config.openai.api_key = Some(api_key);
-    }
-    
-    // Generate config file
-    let config_json = serde_json::to_string_pretty(&config)?;
-    fs::write("acommit.json", config_json)?;
-    
-    println!("\n✅ Configuration saved to acommit.json!");
-    println!("You can now run: acommit");
-    
-    Ok(())
+    Ok(Settings { provider, verbose, config: config::Config::default() })
 }
 
 fn print_example_config() {
     println!("Examples:");
-    println!("  acommit --setup                                 # Interactive setup and generate acommit.json");
+    println!("  acommit --setup                                 # Create or edit a repository or global config");
     println!("  acommit --example-config                         # Show example config format");
+    println!("  acommit models                                   # List models of every configured provider");
+    println!("  acommit doctor                                   # Diagnose configuration problems");
+    println!("  acommit hook install                             # Suggest messages on plain `git commit`");
+    println!("  acommit hook install commit-msg                  # Check hand-written messages against conventions");
+    println!("  acommit prompt render                            # Print the prompt without calling a model");
+    println!("  acommit reword main..HEAD                        # Clean up \"wip\" messages before merging");
+    println!("  acommit squash --onto main                       # Squash a feature branch into one commit");
+    println!("  acommit pr --output pr.md                        # Pull request title and description");
+    println!("  acommit changelog v1.2.0..v1.3.0 --prepend       # Add release notes to CHANGELOG.md");
+    println!("  acommit bump --tag                               # Tag the next version with an AI-written message");
+    println!("  acommit branch \"add csv export\" --ticket PROJ-9  # Create feat/PROJ-9-add-csv-export");
+    println!("  acommit explain HEAD~3                           # What a commit changed, in plain words");
+    println!("  acommit review --block                           # Fail on likely bugs in the staged changes");
+    println!("  acommit summary --since yesterday --author me    # What you did, for the standup");
     println!("  acommit --config acommit.json                    # Use config file with default provider");
     println!("  acommit --config acommit.json --provider ollama  # Use config file with specific provider");
-    println!("  acommit # Auto-detect acommit.json or use ACOMMIT_CONFIG env var or default Ollama");
+    println!("  acommit # Use the repository's acommit.json, else the global config, else default Ollama");
     println!("  acommit --ollama-url http://localhost:11434       # Use local Ollama");
     println!("  acommit --openai http://localhost:8080/v1 --model bitnet-model # Use OpenAI-compatible API");
     println!("  acommit --openai http://api.openai.com/v1 --openai-key sk-xxx --model gpt-4 # Use OpenAI with API key");
     println!("  acommit --model llama3.2:3b                       # Specify model");
+    println!("  acommit --ticket PROJ-123                         # Start the subject with [PROJ-123]");
+    println!("  acommit --signoff --pair alice                    # Add Signed-off-by and Co-authored-by trailers");
+    println!("  acommit -S --no-verify -- --allow-empty           # Pass options on to git commit");
+    println!("  acommit --amend                                  # Replace a quick \"wip\" message of the last commit");
     println!("  acommit --gemini-key xyz --model gemini-2.5-flash # Use Gemini with specific key");
     println!("  acommit -ou http://server:11434 -m codellama:7b   # Remote Ollama with CodeLlama");
     println!("  acommit --verbose --openai http://localhost:8080/v1 # Show debug info");
     println!();
     println!("Environment Variables:");
-    println!("  ACOMMIT_CONFIG              Path to default config file");
+    println!("  ACOMMIT_CONFIG              Path to the config file to use when there is no --config");
     println!("  GEMINI_API_KEY              Used as fallback if no provider specified");
     println!("  OPENAI_API_KEY              Used for OpenAI-compatible APIs when --openai-key not provided");
     println!();
-    println!("Config File Format (JSON):");
+    println!("Config File Format (JSON, values may use ${{VAR}} or ${{VAR:-default}}, provider sections are optional):");
     println!("  {{");
     println!("    \"default_provider\": \"openai\",");
     println!("    \"verbose\": true,");
@@ -709,23 +579,93 @@ fn print_example_config() {
     println!("      \"model\": \"bitnet-model\",");
     println!("      \"url\": \"http://localhost:7777/v1\",");
     println!("      \"api_key\": \"your-openai-key\"");
+    println!("    }},");
+    println!("    \"conventions\": {{");
+    println!("      \"types\": [\"feat\", \"fix\", \"docs\", \"refactor\", \"test\", \"chore\"],");
+    println!("      \"scopes\": \"auto\",");
+    println!("      \"emoji\": {{ \"feat\": \"✨\", \"fix\": \"🐛\" }},");
+    println!("      \"ticket_prefix\": false,");
+    println!("      \"max_subject_length\": 50,");
+    println!("      \"casing\": \"lower\",");
+    println!("      \"imperative\": true,");
+    println!("      \"body_wrap\": 72");
+    println!("    }},");
+    println!("    \"ticket\": {{");
+    println!("      \"pattern\": \"[A-Z][A-Z0-9_]*-\\\\d+\",");
+    println!("      \"placement\": \"prefix\"");
+    println!("    }},");
+    println!("    \"trailers\": {{");
+    println!("      \"signoff\": false,");
+    println!("      \"pairs\": {{ \"alice\": \"Alice Smith <alice@example.com>\" }},");
+    println!("      \"pair_with\": [],");
+    println!("      \"custom\": [\"Reviewed-by: Platform Team <platform@example.com>\"]");
+    println!("    }},");
+    println!("    \"branch\": {{");
+    println!("      \"pattern\": \"{{{{type}}}}/{{{{ticket}}}}-{{{{slug}}}}\"");
+    println!("    }},");
+    println!("    \"review\": {{");
+    println!("      \"enabled\": false,");
+    println!("      \"block_on\": \"high\"");
+    println!("    }},");
+    println!("    \"summary\": {{");
+    println!("      \"repositories\": [\"${{HOME}}/src/api\", \"${{HOME}}/src/web\"]");
+    println!("    }},");
+    println!("    \"prompt\": {{");
+    println!("      \"language\": \"English\",");
+    println!("      \"examples\": 10,");
+    println!("      \"examples_from\": \"paths\",");
+    println!("      \"system\": \"You write commit messages for the {{{{branch}}}} branch.\",");
+    println!("      \"user\": \"Changes:\\n{{{{files}}}}\\n\\nLike these:\\n{{{{examples}}}}\\n\\nRules:\\n{{{{rules}}}}\"");
     println!("    }}");
     println!("  }}");
 }
 
 fn print_usage() {
     println!("Usage: acommit [OPTIONS]");
+    println!("       acommit models [OPTIONS]    List models available from the configured providers");
+    println!("       acommit doctor [OPTIONS]    Check git, config, provider and hooks");
+    println!("       acommit hook install|uninstall [prepare-commit-msg|commit-msg] [--force]");
+    println!("                                   Manage git hooks: generate messages, or lint your own");
+    println!("       acommit prompt render       Print the prompt for the current changes");
+    println!("       acommit reword <RANGE> [--force]");
+    println!("                                   Regenerate the messages of a range of commits on this branch");
+    println!("       acommit squash [--onto <BRANCH>]");
+    println!("                                   One message for the branch since it left BRANCH, then squash");
+    println!("       acommit pr [--base <BRANCH>] [--output <FILE>]");
+    println!("                                   Pull request title and description for this branch");
+    println!("       acommit changelog <FROM>..<TO> [--format markdown|json] [--version <NAME>] [--prepend]");
+    println!("                                   Release notes for a range of commits, in Keep a Changelog format");
+    println!("       acommit bump [--api] [--tag]");
+    println!("                                   Suggest the semver bump since the last tag, and optionally tag it");
+    println!("       acommit branch [DESCRIPTION] Name a branch after a task, or the uncommitted changes, and switch to it");
+    println!("       acommit explain <REV>       Explain what a commit or range of commits changed and why it matters");
+    println!("       acommit review [--json] [--block]");
+    println!("                                   Review the staged changes for bugs, debug output, TODOs and missing tests");
+    println!("       acommit summary [--since <WHEN>] [--author <me|NAME>]");
+    println!("                                   Summarize recent commits, across the repositories in the config");
     println!();
     println!("OPTIONS:");
     println!("  --config <PATH>             Use configuration from JSON file");
     println!("  --provider <PROVIDER>      Override default provider (gemini, ollama, openai)");
-    println!("  --setup                     Interactive setup and generate acommit.json");
+    println!("  --setup                     Interactive setup; edits an existing config and tests the provider");
     println!("  --example-config            Show example configuration file format");
     println!("  --gemini-key, -gk <KEY>     Use Gemini API with provided key");
     println!("  --ollama-url, -ou <URL>     Use Ollama at specified URL");
     println!("  --openai <URL>              Use OpenAI-compatible API at specified URL");
     println!("  --openai-key, -ok <KEY>     API key for OpenAI-compatible API (optional)");
     println!("  --model, -m <MODEL>         Model name to use");
+    println!("  --ticket <ID>               Ticket ID, e.g. PROJ-123 (default: found in the branch name)");
+    println!("  --signoff                   Add a Signed-off-by trailer");
+    println!("  --pair <NAME>               Add a Co-authored-by trailer (alias from config, or \"Name <email>\")");
+    println!("  -S[<KEYID>], --gpg-sign[=<KEYID>], --no-gpg-sign");
+    println!("                              Sign the commit (commit.gpgsign is honoured too)");
+    println!("  --no-verify                 Skip git's pre-commit and commit-msg hooks");
+    println!("  --author <AUTHOR>           Override the commit author");
+    println!("  --date <DATE>               Override the author date");
+    println!("  -- <GIT ARGS>               Pass the remaining arguments to git commit");
+    println!("  --amend                     Regenerate the last commit's message, including staged changes");
+    println!("  --review, --no-review       Review the changes with AI before generating the message (or don't)");
+    println!("  --reason <TEXT>             Why a commit is being reverted, when finishing a git revert");
     println!("  --verbose                   Show debug information");
     println!();
     println!("For example configuration, use: acommit --example-config");
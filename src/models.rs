@@ -0,0 +1,69 @@
+use crate::config;
+use crate::providers::{self, ModelProvider};
+use crate::redact;
+
+/// `acommit models`: lists the models each configured provider offers and
+/// marks the one acommit is set up to use.
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (config_path, selected_provider) = crate::config_args(args)?;
+
+    // Without a config file there is just the provider given by flags or defaults
+    let Some(config_file) = config::config_file(config_path.as_deref())? else {
+        let provider = crate::parse_args(args).await?.provider;
+        return print_models(&provider, true).await;
+    };
+
+    let config = config::load_config(&config_file)?;
+    let names: Vec<String> = match selected_provider {
+        Some(name) => vec![name],
+        None => ["gemini", "ollama", "openai"]
+            .iter()
+            .filter(|name| config.provider_config(name).is_some())
+            .map(|name| name.to_string())
+            .collect(),
+    };
+
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        match config::config_to_provider(&config, Some(name)) {
+            Ok((provider, _)) => {
+                print_models(&provider, *name == config.default_provider).await?;
+            },
+            Err(e) => println!("⚠️  {}: {}", name, e),
+        }
+    }
+
+    Ok(())
+}
+
+async fn print_models(provider: &ModelProvider, is_default: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let header = match provider {
+        ModelProvider::Gemini { .. } => "🧠 Gemini".to_string(),
+        ModelProvider::Ollama { base_url, .. } => format!("🦙 Ollama at {}", base_url),
+        ModelProvider::OpenAI { base_url, .. } => format!("🤖 OpenAI at {}", base_url),
+    };
+    println!("{}{}:", header, if is_default { " (default provider)" } else { "" });
+
+    match providers::list_models(provider).await {
+        Ok(models) => {
+            if models.is_empty() {
+                println!("  (no models reported)");
+            }
+            for model in &models {
+                if model == provider.model() {
+                    println!("  * {} (configured)", model);
+                } else {
+                    println!("    {}", model);
+                }
+            }
+            if !models.is_empty() && !models.iter().any(|m| m == provider.model()) {
+                println!("  ⚠️  Configured model {} is not in this list", provider.model());
+            }
+        },
+        Err(e) => println!("  ❌ Could not list models: {}", redact::redact(&e.to_string())),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Commits that finish a merge, revert or cherry-pick. Git's prepared message
+//! is kept, and the model adds how conflicts were resolved or why a commit
+//! was reverted.
+
+use std::fmt;
+use std::fs;
+use crate::config::Config;
+use crate::conventional;
+use crate::git;
+use crate::lint;
+use crate::prompt;
+use crate::providers::{self, ModelProvider, Prompt};
+use crate::ticket;
+use crate::trailers;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Merge,
+    Revert,
+    CherryPick,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Merge => write!(f, "merge"),
+            Kind::Revert => write!(f, "revert"),
+            Kind::CherryPick => write!(f, "cherry-pick"),
+        }
+    }
+}
+
+/// A merge, revert or cherry-pick waiting for its commit.
+pub struct Operation {
+    pub kind: Kind,
+    /// The commit being merged, reverted or picked; the first one for an
+    /// octopus merge.
+    pub commit: String,
+    /// Git's prepared message, without comments or the conflicts list.
+    pub message: String,
+    /// Files that had conflicts, from git's prepared message.
+    pub conflicts: Vec<String>,
+}
+
+/// The operation in progress, if any. A cherry-pick or revert that stopped
+/// in the middle of a sequence is found before a plain merge.
+pub fn detect() -> Result<Option<Operation>, Box<dyn std::error::Error>> {
+    let states = [
+        ("CHERRY_PICK_HEAD", Kind::CherryPick),
+        ("REVERT_HEAD", Kind::Revert),
+        ("MERGE_HEAD", Kind::Merge),
+    ];
+    let Some((head, kind)) = states
+        .into_iter()
+        .find_map(|(file, kind)| Some((fs::read_to_string(git::git_path(file)?).ok()?, kind)))
+    else {
+        return Ok(None);
+    };
+    let commit = head.split_whitespace().next().ok_or("The operation's head file is empty")?.to_string();
+
+    let prepared = git::git_path("MERGE_MSG").and_then(|path| fs::read_to_string(path).ok());
+    let (message, conflicts) = match prepared.as_deref().map(split_conflicts) {
+        Some((message, conflicts)) if !message.is_empty() => (message, conflicts),
+        // Without MERGE_MSG, write what git would have
+        _ => (default_message(kind, &commit)?, Vec::new()),
+    };
+    Ok(Some(Operation { kind, commit, message, conflicts }))
+}
+
+/// Separates git's `Conflicts:` list from the prepared message. Git writes
+/// it as comments (`# Conflicts:` then `#\tfile`), older versions as text.
+fn split_conflicts(prepared: &str) -> (String, Vec<String>) {
+    let mut message = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut in_list = false;
+    for line in prepared.lines() {
+        let uncommented = line.strip_prefix('#').map(|l| l.strip_prefix(' ').unwrap_or(l)).unwrap_or(line);
+        if uncommented.trim_end() == "Conflicts:" {
+            in_list = true;
+            continue;
+        }
+        if in_list {
+            if let Some(file) = uncommented.strip_prefix('\t') {
+                conflicts.push(file.trim().to_string());
+                continue;
+            }
+            in_list = false;
+        }
+        message.push(line);
+    }
+    (lint::strip_comments(&message.join("\n")), conflicts)
+}
+
+fn default_message(kind: Kind, commit: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match kind {
+        Kind::Merge => format!("Merge commit '{}'", git::short(commit)),
+        Kind::Revert => {
+            let subject = git::git(&["log", "-1", "--format=%s", commit])?;
+            format!("Revert \"{}\"\n\nThis reverts commit {}.", subject.trim(), commit)
+        },
+        Kind::CherryPick => git::git(&["log", "-1", "--format=%B", commit])?.trim().to_string(),
+    })
+}
+
+/// Files that still have conflict markers or are unmerged in a way `git add`
+/// can't settle (deleted on one side and modified on the other).
+pub fn unresolved(operation: &Operation) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let unmerged = git::git(&["diff", "--name-only", "--diff-filter=U"])?;
+    let mut files: Vec<String> = operation.conflicts.clone();
+    for file in unmerged.lines().map(str::trim).filter(|f| !f.is_empty()) {
+        if !files.iter().any(|f| f == file) {
+            files.push(file.to_string());
+        }
+    }
+    let root = git::repo_root().ok_or("Not in a git repository")?;
+    Ok(files
+        .into_iter()
+        .filter(|file| match fs::read(root.join(file)) {
+            Ok(content) => has_markers(&String::from_utf8_lossy(&content)),
+            // A file that is gone is resolved if git knows it's gone
+            Err(_) => unmerged.lines().any(|f| f.trim() == file),
+        })
+        .collect())
+}
+
+fn has_markers(content: &str) -> bool {
+    content.lines().any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+}
+
+/// `--reason <TEXT>`: why a commit is being reverted.
+pub fn reason_arg(args: &[String]) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    for (i, arg) in args.iter().enumerate().skip(1) {
+        if let Some(value) = arg.strip_prefix("--reason=") {
+            return Ok(Some(value.to_string()));
+        } else if arg == "--reason" {
+            return Ok(Some(args.get(i + 1).ok_or("--reason requires a value")?.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// The message that finishes `operation`: git's first line and text, with a
+/// generated paragraph on the conflict resolutions or the revert, then the
+/// usual trailers. A merge or cherry-pick without conflicts needs no model.
+pub async fn compose_message(provider: &ModelProvider, config: &Config, args: &[String], operation: &Operation) -> Result<String, Box<dyn std::error::Error>> {
+    let reason = reason_arg(args)?;
+    if reason.is_some() && operation.kind != Kind::Revert {
+        println!("⚠️  --reason only applies to reverts, ignoring it");
+    }
+
+    let explanation = if operation.kind == Kind::Revert || !operation.conflicts.is_empty() {
+        println!("🤖 Explaining the {} with AI...", operation.kind);
+        let prompt = operation_prompt(config, operation, reason.as_deref())?;
+        let answer = providers::generate(provider, &prompt).await?;
+        let answer = conventional::strip_wrapping(&conventional::strip_think_blocks(&answer));
+        Some(answer.trim().to_string()).filter(|a| !a.is_empty())
+    } else {
+        println!("💡 There were no conflicts, keeping git's {} message", operation.kind);
+        None
+    };
+
+    let mut message = operation.message.clone();
+    if let Some(explanation) = explanation {
+        // Wrap only the new paragraph; git's text stays as it was written
+        message = insert_paragraph(&message, &lint::rewrap(&explanation, config.conventions().body_wrap))?;
+    }
+
+    let ticket = ticket::resolve(args, config)?;
+    let trailers = trailers::collect(args, config, ticket.as_deref())?;
+    trailers::append(&message, &trailers)
+}
+
+/// Adds `paragraph` to the end of the body, above any trailer block.
+fn insert_paragraph(message: &str, paragraph: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let message = message.trim_end();
+    if let Some((body, last)) = message.rsplit_once("\n\n") {
+        if last.starts_with("(cherry picked from commit ") || !trailers::parse(message)?.is_empty() {
+            return Ok(format!("{}\n\n{}\n\n{}", body, paragraph, last));
+        }
+    }
+    Ok(format!("{}\n\n{}", message, paragraph))
+}
+
+fn operation_prompt(config: &Config, operation: &Operation, reason: Option<&str>) -> Result<Prompt, Box<dyn std::error::Error>> {
+    let language = prompt::language(config);
+    let original = git::git(&["log", "-1", "--format=%B", &operation.commit])?;
+    let rules = format!(
+        "Rules:\n\
+        - Write one short plain-text paragraph in {}, no headings or bullet lists\n\
+        - Don't repeat the commit message or list every changed line\n\
+        - Only return the paragraph, nothing else",
+        language
+    );
+
+    let resolutions = if operation.conflicts.is_empty() {
+        String::new()
+    } else {
+        // The resolved files compared with both sides show what each side gave up.
+        // MERGE_MSG lists them relative to the repository root, not the current directory
+        let paths: Vec<String> = operation.conflicts.iter().map(|file| format!(":(top,literal){}", file)).collect();
+        let mut ours = vec!["diff", "HEAD", "--"];
+        let mut theirs = vec!["diff", operation.commit.as_str(), "--"];
+        for path in &paths {
+            ours.push(path);
+            theirs.push(path);
+        }
+        format!(
+            "These files had conflicts: {}\n\n\
+            The resolution compared with the current branch:\n{}\n\n\
+            The resolution compared with {}:\n{}\n\n",
+            operation.conflicts.join(", "),
+            prompt::truncate_diff(&git::git(&ours)?),
+            git::short(&operation.commit),
+            prompt::truncate_diff(&git::git(&theirs)?)
+        )
+    };
+
+    let text = match operation.kind {
+        Kind::Merge => format!(
+            "A git merge had conflicts that were resolved by hand. The merge commit's message is:\n{}\n\n\
+            The merged commit's message is:\n{}\n\n{}\
+            Explain how the conflicts were resolved: which side's changes were kept or how they were combined, and why if it's evident.\n\n{}",
+            operation.message, original.trim(), resolutions, rules
+        ),
+        Kind::CherryPick => format!(
+            "A commit was cherry-picked and its conflicts were resolved by hand. Its message is:\n{}\n\n{}\
+            Explain how the conflicts were resolved: what had to change for the commit to apply here.\n\n{}",
+            original.trim(), resolutions, rules
+        ),
+        Kind::Revert => {
+            let reverted = prompt::Changes::between(&format!("{}^", operation.commit), &operation.commit)
+                .or_else(|_| prompt::Changes::between(&git::empty_tree()?, &operation.commit))?;
+            let why = match reason {
+                Some(reason) => format!("The reason for reverting it, in the developer's words: {}\n\nState this reason clearly.", reason),
+                None => "No reason was given, so don't invent one; say what reverting it undoes.".to_string(),
+            };
+            format!(
+                "This commit is being reverted:\n{}\n\nIts diff:\n{}\n\n{}{}\n\n\
+                Explain the revert for the commit message body: what behaviour goes away.\n\n{}",
+                original.trim(), prompt::truncate_diff(&reverted.diff), resolutions, why, rules
+            )
+        },
+    };
+    Ok(Prompt::user(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicts_are_read_from_comments() {
+        let prepared = "Merge branch 'feature'\n\n# Conflicts:\n#\tsrc/a.rs\n#\tdocs/b c.md\n#\n# It looks like you may be committing a merge.\n";
+        assert_eq!(
+            split_conflicts(prepared),
+            ("Merge branch 'feature'".to_string(), vec!["src/a.rs".to_string(), "docs/b c.md".to_string()])
+        );
+    }
+
+    #[test]
+    fn conflicts_are_read_from_plain_text() {
+        let prepared = "Merge branch 'feature'\n\nConflicts:\n\tsrc/a.rs\n\tsrc/b.rs\n";
+        assert_eq!(
+            split_conflicts(prepared),
+            ("Merge branch 'feature'".to_string(), vec!["src/a.rs".to_string(), "src/b.rs".to_string()])
+        );
+    }
+
+    #[test]
+    fn a_message_without_conflicts_is_kept() {
+        let prepared = "Revert \"feat: add export\"\n\nThis reverts commit 0123456789abcdef.\n\n# Please enter the commit message.\n";
+        assert_eq!(
+            split_conflicts(prepared),
+            ("Revert \"feat: add export\"\n\nThis reverts commit 0123456789abcdef.".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn the_cherry_pick_line_and_trailers_stay_last() {
+        let prepared = "fix: handle empty input\n\nThe parser no longer panics.\n\n\
+            Signed-off-by: Alice <a@x.com>\n(cherry picked from commit 0123456789abcdef)\n\n\
+            # Conflicts:\n#\tsrc/parser.rs\n";
+        let (message, conflicts) = split_conflicts(prepared);
+        assert_eq!(conflicts, ["src/parser.rs"]);
+        assert_eq!(
+            insert_paragraph(&message, "Kept this branch's error type.").unwrap(),
+            "fix: handle empty input\n\nThe parser no longer panics.\n\nKept this branch's error type.\n\n\
+            Signed-off-by: Alice <a@x.com>\n(cherry picked from commit 0123456789abcdef)"
+        );
+    }
+
+    #[test]
+    fn the_paragraph_goes_above_a_lone_cherry_pick_line() {
+        assert_eq!(
+            insert_paragraph("fix: x\n\n(cherry picked from commit 0123456789abcdef)", "Resolved.").unwrap(),
+            "fix: x\n\nResolved.\n\n(cherry picked from commit 0123456789abcdef)"
+        );
+    }
+
+    #[test]
+    fn the_paragraph_goes_at_the_end_without_trailers() {
+        assert_eq!(
+            insert_paragraph("Merge branch 'feature'\n", "Both sides' changes were kept.").unwrap(),
+            "Merge branch 'feature'\n\nBoth sides' changes were kept."
+        );
+        assert_eq!(
+            insert_paragraph("Revert \"x\"\n\nThis reverts commit 0123.", "It broke the build.").unwrap(),
+            "Revert \"x\"\n\nThis reverts commit 0123.\n\nIt broke the build."
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! `acommit pr`: a pull request title and description for the current
+//! branch, following the repository's PR template if it has one.
+
+use std::fs;
+use std::path::PathBuf;
+use crate::conventional;
+use crate::git;
+use crate::prompt::{self, Changes};
+use crate::providers::{self, Prompt};
+use crate::ticket;
+
+// Where GitHub and GitLab look for a pull request template
+const TEMPLATE_FILES: [&str; 7] = [
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+    "docs/PULL_REQUEST_TEMPLATE.md",
+    ".gitlab/merge_request_templates/Default.md",
+];
+
+const DEFAULT_SECTIONS: &str = "Use these sections in the description:\n## Summary\nWhat the change does and why, in 1-3 sentences.\n## Changes\nA bullet list of the notable changes.\n## Testing\nHow the changes were or can be tested. Don't claim tests were run unless the commits say so.";
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = crate::parse_args(args).await?;
+
+    let base = match crate::value_arg(args, "--base")? {
+        Some(base) => base,
+        None => git::default_branch().ok_or("Could not tell which branch the PR targets, pass it with --base <BRANCH>")?,
+    };
+    let merge_base = git::git(&["merge-base", &base, "HEAD"])
+        .map_err(|_| format!("HEAD and {} have no common history", base))?
+        .trim()
+        .to_string();
+    let mut changes = Changes::between(&merge_base, "HEAD")?;
+    changes.commits = git::commit_messages(&format!("{}..HEAD", merge_base))?;
+    if changes.commits.is_empty() {
+        return Err(format!("HEAD has no commits that aren't on {}, there is nothing to describe", base).into());
+    }
+
+    // Progress goes to stderr so stdout can be piped, e.g. into `gh pr create`
+    eprintln!("🔀 {} commit(s) compared with {}", changes.commits.len(), base);
+    let template = find_template();
+    if let Some((path, _)) = &template {
+        eprintln!("📄 Following the PR template in {}", path.display());
+    }
+    let ticket = ticket::resolve(args, &settings.config)?;
+
+    let prompt = pr_prompt(&settings.config, &changes, &base, template.as_ref().map(|(_, t)| t.as_str()), ticket.as_deref());
+    eprintln!("🤖 Generating pull request description with AI...");
+    let answer = providers::generate(&settings.provider, &prompt).await?;
+    let (title, body) = split_answer(&answer)?;
+    let output = format!("{}\n\n{}\n", title, body);
+
+    match crate::value_arg(args, "--output")? {
+        Some(path) => {
+            fs::write(&path, &output).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            eprintln!("✅ Wrote the title and description to {}", path);
+        },
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// The repository's pull request template and its path, if it has one.
+fn find_template() -> Option<(PathBuf, String)> {
+    let root = git::repo_root()?;
+    TEMPLATE_FILES.iter().find_map(|name| {
+        let path = root.join(name);
+        let text = fs::read_to_string(&path).ok()?;
+        Some((path, text))
+    })
+}
+
+fn pr_prompt(config: &crate::config::Config, changes: &Changes, base: &str, template: Option<&str>, ticket: Option<&str>) -> Prompt {
+    let commits: Vec<String> = changes.commits.iter().map(|m| prompt::commit_item(m)).collect();
+    let branch = git::current_branch().unwrap_or_else(|| "HEAD".to_string());
+    let mut user = format!(
+        "Write a pull request title and description in {} for branch {}, which is to be merged into {}.\n\nCommits:\n{}\n\nChanged files:\n{}\n\nDiff:\n{}\n\n",
+        prompt::language(config), branch, base, commits.join("\n"), changes.files.trim(), prompt::truncate_diff(&changes.diff)
+    );
+    if let Some(ticket) = ticket {
+        user.push_str(&format!("The changes are for ticket {}; mention it in the description.\n\n", ticket));
+    }
+    match template {
+        Some(template) => user.push_str(&format!(
+            "Fill in this pull request template for the description, keeping its headings and checkboxes and removing its HTML comments:\n{}\n\n",
+            template.trim()
+        )),
+        None => user.push_str(&format!("{}\n\n", DEFAULT_SECTIONS)),
+    }
+    user.push_str("Answer with the title on the first line (plain text, at most 72 characters), an empty line, then the description in markdown. Only return the title and description, nothing else.");
+    Prompt::user(user)
+}
+
+/// Splits the model's answer into the title and the markdown description.
+fn split_answer(answer: &str) -> Result<(String, String), Box<dyn std::error::Error>> {
+    let answer = conventional::strip_think_blocks(answer);
+    let mut lines: Vec<&str> = answer.trim().lines().collect();
+    // Drop a fence around the whole answer, but not code blocks inside it
+    if lines.first().is_some_and(|l| l.starts_with("```")) && lines.last().is_some_and(|l| l.trim() == "```") && lines.len() > 1 {
+        lines = lines[1..lines.len() - 1].to_vec();
+    }
+
+    let start = lines.iter().position(|l| !l.trim().is_empty()).ok_or("The model returned an empty answer")?;
+    let title = lines[start].trim().trim_start_matches('#').trim();
+    let title = title.strip_prefix("Title:").unwrap_or(title);
+    let title = conventional::strip_wrapping(title);
+    if title.is_empty() {
+        return Err("The model's answer has no title".into());
+    }
+
+    let body = lines[start + 1..].join("\n");
+    let body = body.trim();
+    let body = body.strip_prefix("Description:").unwrap_or(body).trim();
+    Ok((title, body.to_string()))
+}
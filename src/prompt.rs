@@ -0,0 +1,331 @@
+//! Prompt templates: the built-in one, the config's `prompt` section, or
+//! `.acommit/prompt.md` in the repository.
+
+use std::fs;
+use std::path::PathBuf;
+use crate::config::{Casing, Config, Conventions, ExampleSource};
+use crate::git;
+use crate::lint;
+use crate::providers::Prompt;
+use crate::ticket;
+
+pub const TEMPLATE_FILE: &str = ".acommit/prompt.md";
+
+// The built-in user prompt; the ticket and examples parts only appear when there are any
+const DEFAULT_INTRO: &str = "Generate a concise, clear git commit message in {{language}} based on these file changes:\n\n{{files}}\n\n";
+const DEFAULT_COMMITS: &str = "They combine these commits; summarize them in one message, leaving out fixups and work that was undone later:\n{{commits}}\n\n";
+const DEFAULT_TICKET: &str = "These changes are for ticket {{ticket}}.\n\n";
+const DEFAULT_EXAMPLES: &str = "Match the style of these earlier commit messages from this repository (casing, scopes, tense, ticket format):\n{{examples}}\n\n";
+const DEFAULT_RULES: &str = "Rules:\n{{rules}}\n- Be specific but concise\n- Only return the commit message, nothing else";
+
+// Longer diffs are cut so the prompt fits small local models
+const MAX_DIFF_CHARS: usize = 12_000;
+
+const RECENT_COMMITS: usize = 10;
+
+/// The changes a message is generated for.
+pub struct Changes {
+    /// `--name-status` listing.
+    pub files: String,
+    pub diff: String,
+    /// Messages of the commits being combined, when squashing.
+    pub commits: Vec<String>,
+}
+
+impl Changes {
+    /// Staged changes. With `fallback_unstaged`, the working tree's changes
+    /// when nothing is staged.
+    pub fn collect(fallback_unstaged: bool) -> Result<Changes, Box<dyn std::error::Error>> {
+        let files = git::git(&["diff", "--cached", "--name-status"])?;
+        if !files.trim().is_empty() || !fallback_unstaged {
+            let diff = git::git(&["diff", "--cached"])?;
+            return Ok(Changes { files, diff, commits: Vec::new() });
+        }
+        Ok(Changes {
+            files: git::git(&["diff", "--name-status"])?,
+            diff: git::git(&["diff"])?,
+            commits: Vec::new(),
+        })
+    }
+
+    /// Staged changes compared with `base` rather than `HEAD`, so amending
+    /// covers the last commit too.
+    pub fn against(base: &str) -> Result<Changes, Box<dyn std::error::Error>> {
+        Ok(Changes {
+            files: git::git(&["diff", "--cached", "--name-status", base])?,
+            diff: git::git(&["diff", "--cached", base])?,
+            commits: Vec::new(),
+        })
+    }
+
+    /// What changed from `from` to `to`, for describing an existing commit.
+    pub fn between(from: &str, to: &str) -> Result<Changes, Box<dyn std::error::Error>> {
+        Ok(Changes {
+            files: git::git(&["diff", "--name-status", from, to])?,
+            diff: git::git(&["diff", from, to])?,
+            commits: Vec::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.trim().is_empty()
+    }
+}
+
+/// A prompt before its `{{variables}}` are filled in.
+pub struct Template {
+    pub system: Option<String>,
+    pub user: String,
+    /// Where the template came from, for messages.
+    pub source: String,
+}
+
+/// The config's `prompt` section if it sets a template, otherwise
+/// `.acommit/prompt.md` at the repository root, otherwise the built-in prompt.
+pub fn load_template(config: &Config, changes: &Changes, ticket: Option<&str>) -> Result<Template, Box<dyn std::error::Error>> {
+    if let Some(prompt) = config.prompt.as_ref().filter(|p| p.system.is_some() || p.user.is_some()) {
+        return Ok(Template {
+            system: prompt.system.clone(),
+            user: prompt.user.clone().unwrap_or_else(|| default_user(config, changes, ticket)),
+            source: "config".to_string(),
+        });
+    }
+
+    let path = git::repo_root().unwrap_or_else(|| PathBuf::from(".")).join(TEMPLATE_FILE);
+    if path.exists() {
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let (system, user) = parse_template_file(&text);
+        return Ok(Template {
+            system,
+            user: user.unwrap_or_else(|| default_user(config, changes, ticket)),
+            source: path.display().to_string(),
+        });
+    }
+
+    Ok(Template { system: None, user: default_user(config, changes, ticket), source: "built-in".to_string() })
+}
+
+fn default_user(config: &Config, changes: &Changes, ticket: Option<&str>) -> String {
+    let commits = if changes.commits.is_empty() { "" } else { DEFAULT_COMMITS };
+    let ticket = if ticket.is_some() { DEFAULT_TICKET } else { "" };
+    let examples = if example_count(config) > 0 { DEFAULT_EXAMPLES } else { "" };
+    format!("{}{}{}{}{}", DEFAULT_INTRO, commits, ticket, examples, DEFAULT_RULES)
+}
+
+fn example_count(config: &Config) -> usize {
+    config.prompt.as_ref().and_then(|p| p.examples).unwrap_or(0)
+}
+
+/// Subjects of earlier commits for the model to imitate: the latest ones, or
+/// with `examples_from: "paths"` the latest touching the changed files.
+fn style_examples(config: &Config, changes: &Changes) -> Vec<String> {
+    let count = example_count(config);
+    if count == 0 {
+        return Vec::new();
+    }
+
+    // Ask for more than needed, as merges and fixups are skipped
+    let limit = format!("-{}", count * 2);
+    let from_paths = config.prompt.as_ref().and_then(|p| p.examples_from) == Some(ExampleSource::Paths);
+    let mut examples = Vec::new();
+    if from_paths {
+        // The listing's paths are relative to the repository root, not the current directory
+        let paths: Vec<String> = changes
+            .files
+            .lines()
+            .filter_map(|line| line.split('\t').next_back())
+            .filter(|path| !path.is_empty())
+            .map(|path| format!(":(top,literal){}", path))
+            .collect();
+        if !paths.is_empty() {
+            let mut args = vec!["log", &limit, "--no-merges", "--format=%s", "--"];
+            args.extend(paths.iter().map(String::as_str));
+            examples = subjects(&args, count);
+        }
+    }
+    if examples.is_empty() {
+        examples = subjects(&["log", &limit, "--no-merges", "--format=%s"], count);
+    }
+    examples
+}
+
+fn subjects(args: &[&str], count: usize) -> Vec<String> {
+    let log = git::git(args).unwrap_or_default();
+    let mut subjects: Vec<String> = Vec::new();
+    for subject in log.lines().map(str::trim) {
+        if !subject.is_empty() && !lint::is_exempt(subject) && !subjects.iter().any(|s| s == subject) {
+            subjects.push(subject.to_string());
+        }
+    }
+    subjects.truncate(count);
+    subjects
+}
+
+/// Splits a template file into its `# System` and `# User` sections. A file
+/// without those headings is all user prompt.
+fn parse_template_file(text: &str) -> (Option<String>, Option<String>) {
+    let mut system: Option<Vec<&str>> = None;
+    let mut user: Option<Vec<&str>> = None;
+    let mut current: Option<&mut Vec<&str>> = None;
+    let mut has_headings = false;
+
+    for line in text.lines() {
+        let heading = line.trim().trim_start_matches('#').trim().to_lowercase();
+        if line.trim_start().starts_with('#') && (heading == "system" || heading == "user") {
+            has_headings = true;
+            current = Some(if heading == "system" {
+                system.insert(Vec::new())
+            } else {
+                user.insert(Vec::new())
+            });
+        } else if let Some(section) = current.as_mut() {
+            section.push(line);
+        }
+    }
+
+    if !has_headings {
+        return (None, Some(text.trim().to_string()));
+    }
+    let join = |lines: Option<Vec<&str>>| {
+        lines.map(|l| l.join("\n").trim().to_string()).filter(|t| !t.is_empty())
+    };
+    (join(system), join(user))
+}
+
+/// Fills in `{{name}}` placeholders; unknown names are an error.
+pub fn render(template: &str, variables: &[(&str, String)]) -> Result<String, String> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        let name = rest[start + 2..start + end].trim();
+        let value = variables
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v)
+            .ok_or_else(|| format!(
+                "Unknown template variable {{{{{}}}}} (available: {})",
+                name, variables.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+            ))?;
+        output.push_str(&rest[..start]);
+        output.push_str(value.trim_end());
+        rest = &rest[start + end + 2..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Values of the template variables for `changes`.
+fn variables(config: &Config, changes: &Changes, ticket: Option<&str>) -> Vec<(&'static str, String)> {
+    let recent_commits = git::git(&["log", &format!("-{}", RECENT_COMMITS), "--format=%s"]).unwrap_or_default();
+
+    vec![
+        ("diff", truncate_diff(&changes.diff)),
+        ("files", changes.files.trim().to_string()),
+        ("branch", git::current_branch().unwrap_or_default()),
+        ("recent_commits", recent_commits),
+        ("examples", style_examples(config, changes).join("\n")),
+        ("ticket", ticket.unwrap_or_default().to_string()),
+        ("language", language(config)),
+        ("rules", convention_rules(&config.conventions(), ticket::prefix(config, ticket))),
+        ("commits", changes.commits.iter().map(|m| commit_item(m)).collect::<Vec<_>>().join("\n")),
+    ]
+}
+
+/// `diff` cut to a size that fits the prompts of small local models.
+pub fn truncate_diff(diff: &str) -> String {
+    let mut diff = diff.to_string();
+    if let Some((cut, _)) = diff.char_indices().nth(MAX_DIFF_CHARS) {
+        diff.truncate(cut);
+        diff.push_str("\n... (diff truncated)");
+    }
+    diff
+}
+
+/// The language messages are written in, `English` unless configured.
+pub fn language(config: &Config) -> String {
+    config
+        .prompt
+        .as_ref()
+        .and_then(|p| p.language.clone())
+        .unwrap_or_else(|| "English".to_string())
+}
+
+/// A commit message as a list item, its body indented below the subject.
+pub fn commit_item(message: &str) -> String {
+    let mut lines = message.trim().lines();
+    let mut item = format!("- {}", lines.next().unwrap_or(""));
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+        item.push_str("\n  ");
+        item.push_str(line);
+    }
+    item
+}
+
+/// The prompt asking for a commit message describing `changes`.
+pub fn commit_prompt(config: &Config, changes: &Changes, ticket: Option<&str>) -> Result<Prompt, Box<dyn std::error::Error>> {
+    let template = load_template(config, changes, ticket)?;
+    let variables = variables(config, changes, ticket);
+    let in_source = |e: String| format!("{} (prompt template: {})", e, template.source);
+    let system = match &template.system {
+        Some(system) => Some(render(system, &variables).map_err(in_source)?),
+        None => None,
+    };
+    let user = render(&template.user, &variables).map_err(in_source)?;
+    Ok(Prompt { system, user })
+}
+
+/// `conventions` spelled out for the model. The ticket reference and emoji
+/// are added afterwards, so the model is told to leave them out.
+pub fn convention_rules(conventions: &Conventions, ticket: Option<&str>) -> String {
+    let mut rules = Vec::new();
+    if conventions.types.is_empty() {
+        rules.push("- Write a plain subject line, without a type prefix".to_string());
+    } else {
+        rules.push("- Use conventional commits format: <type>(<optional scope>): <description>".to_string());
+        rules.push(format!("- Allowed types: {}", conventions.types.join(", ")));
+        let scopes = conventions.allowed_scopes();
+        if !scopes.is_empty() {
+            rules.push(format!("- Allowed scopes: {} (or no scope)", scopes.join(", ")));
+        }
+    }
+
+    let budget = conventions.max_subject_length.saturating_sub(lint::prefix_length(conventions, ticket));
+    rules.push(format!("- Maximum {} characters for the title", budget));
+    match conventions.casing {
+        Casing::Lower => rules.push("- Start the description with a lowercase letter".to_string()),
+        Casing::Sentence => rules.push("- Start the description with a capital letter".to_string()),
+        Casing::Any => {},
+    }
+    if conventions.imperative {
+        rules.push("- Start the description with an imperative verb (\"add\", not \"added\")".to_string());
+    }
+    if conventions.ticket_prefix || !conventions.emoji.is_empty() {
+        rules.push("- Don't add emoji or ticket references, they are added automatically".to_string());
+    }
+    rules.join("\n")
+}
+
+/// `acommit prompt render`: prints the prompt a commit would send, without
+/// calling a model.
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if args.get(2).map(String::as_str) != Some("render") {
+        return Err("Usage: acommit prompt render [--config <PATH>] [--ticket <ID>]".into());
+    }
+
+    let config = crate::load_config_from_args(args)?.unwrap_or_default();
+    let changes = Changes::collect(true)?;
+    if changes.is_empty() {
+        eprintln!("⚠️  No changes: the diff and file list are empty");
+    }
+    let ticket = ticket::resolve(args, &config)?;
+
+    eprintln!("📄 Prompt template: {}", load_template(&config, &changes, ticket.as_deref())?.source);
+    let prompt = commit_prompt(&config, &changes, ticket.as_deref())?;
+    if let Some(system) = &prompt.system {
+        println!("# System\n\n{}\n\n# User\n", system);
+    }
+    println!("{}", prompt.user);
+    Ok(())
+}
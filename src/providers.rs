@@ -0,0 +1,440 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::redact;
+
+const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+#[derive(Clone)]
+pub enum ModelProvider {
+    Gemini { api_key: String, model: String },
+    Ollama { base_url: String, model: String },
+    OpenAI { base_url: String, api_key: Option<String>, model: String },
+}
+
+/// What is sent to the model: optional system instructions, and the request.
+#[derive(Debug, Clone, Default)]
+pub struct Prompt {
+    pub system: Option<String>,
+    pub user: String,
+}
+
+impl Prompt {
+    pub fn user(text: impl Into<String>) -> Self {
+        Prompt { system: None, user: text.into() }
+    }
+}
+
+/// A provider answered with an error status.
+#[derive(Debug)]
+pub struct ApiError {
+    pub request: &'static str,
+    pub status: reqwest::StatusCode,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed: {}", self.request, self.status)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+// Hand-written so API keys never show up in `{:?}` output
+impl fmt::Debug for ModelProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelProvider::Gemini { api_key, model } => write!(
+                f, "Gemini {{ api_key: {:?}, model: {:?} }}",
+                redact::mask(api_key), model
+            ),
+            ModelProvider::Ollama { base_url, model } => write!(
+                f, "Ollama {{ base_url: {:?}, model: {:?} }}",
+                base_url, model
+            ),
+            ModelProvider::OpenAI { base_url, api_key, model } => write!(
+                f, "OpenAI {{ base_url: {:?}, api_key: {}, model: {:?} }}",
+                base_url, redact::mask_opt(api_key.as_deref()), model
+            ),
+        }
+    }
+}
+
+impl ModelProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModelProvider::Gemini { .. } => "gemini",
+            ModelProvider::Ollama { .. } => "ollama",
+            ModelProvider::OpenAI { .. } => "openai",
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        match self {
+            ModelProvider::Gemini { model, .. }
+            | ModelProvider::Ollama { model, .. }
+            | ModelProvider::OpenAI { model, .. } => model,
+        }
+    }
+
+    pub fn api_key(&self) -> Option<&str> {
+        match self {
+            ModelProvider::Gemini { api_key, .. } => Some(api_key),
+            ModelProvider::Ollama { .. } => None,
+            ModelProvider::OpenAI { api_key, .. } => api_key.as_deref(),
+        }
+    }
+}
+
+// Gemini API structures
+#[derive(Serialize)]
+struct GeminiRequest {
+    #[serde(rename = "systemInstruction", skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    contents: Vec<GeminiContent>,
+}
+
+#[derive(Serialize)]
+struct GeminiContent {
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Serialize)]
+struct GeminiPart {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct GeminiResponse {
+    candidates: Option<Vec<GeminiCandidate>>,
+}
+
+#[derive(Deserialize)]
+struct GeminiCandidate {
+    content: Option<GeminiResponseContent>,
+}
+
+#[derive(Deserialize)]
+struct GeminiResponseContent {
+    parts: Option<Vec<GeminiResponsePart>>,
+}
+
+#[derive(Deserialize)]
+struct GeminiResponsePart {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModelList {
+    models: Option<Vec<GeminiModel>>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModel {
+    name: String,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
+// Ollama API structures
+#[derive(Serialize)]
+struct OllamaRequest {
+    model: String,
+    prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    response: Option<String>,
+    // done: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct OllamaTags {
+    models: Option<Vec<OllamaTag>>,
+}
+
+#[derive(Deserialize)]
+struct OllamaTag {
+    name: String,
+}
+
+// OpenAI API structures
+#[derive(Serialize)]
+struct OpenAIRequest {
+    model: String,
+    messages: Vec<OpenAIMessage>,
+    max_tokens: Option<u32>,
+    temperature: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct OpenAIMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAIResponse {
+    choices: Option<Vec<OpenAIChoice>>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIChoice {
+    message: Option<OpenAIResponseMessage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIResponseMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIModelList {
+    data: Option<Vec<OpenAIModel>>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIModel {
+    id: String,
+}
+
+async fn call_gemini_api(api_key: &str, model: &str, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    
+    let request_body = GeminiRequest {
+        system_instruction: prompt.system.as_ref().map(|system| GeminiContent {
+            parts: vec![GeminiPart {
+                text: system.clone(),
+            }],
+        }),
+        contents: vec![GeminiContent {
+            parts: vec![GeminiPart {
+                text: prompt.user.clone(),
+            }],
+        }],
+    };
+
+    let url = format!(
+        "{}/models/{}:generateContent",
+        GEMINI_API_BASE, model
+    );
+
+    // The key goes in a header so it never ends up in URLs, logs or error messages
+    let response = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .header("x-goog-api-key", api_key)
+        .json(&request_body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(ApiError { request: "Gemini API request", status: response.status() }.into());
+    }
+
+    let data: GeminiResponse = response.json().await?;
+    
+    let commit_message = data
+        .candidates
+        .and_then(|candidates| candidates.into_iter().next())
+        .and_then(|candidate| candidate.content)
+        .and_then(|content| content.parts)
+        .and_then(|parts| parts.into_iter().next())
+        .and_then(|part| part.text)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    // Cleanup happens in conventional::clean_output, so all providers behave alike
+    Ok(commit_message)
+}
+
+async fn call_ollama_api(base_url: &str, model: &str, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+    
+    let request_body = OllamaRequest {
+        model: model.to_string(),
+        prompt: prompt.user.clone(),
+        system: prompt.system.clone(),
+        stream: false,
+    };
+
+    let url = format!("{}/api/generate", base_url);
+
+    let response = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(ApiError { request: "Ollama API request", status: response.status() }.into());
+    }
+
+    let data: OllamaResponse = response.json().await?;
+    
+    let commit_message = data
+        .response
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    Ok(commit_message)
+}
+
+async fn call_openai_api(base_url: &str, api_key: Option<&String>, model: &str, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+
+    let mut messages = Vec::new();
+    if let Some(system) = &prompt.system {
+        messages.push(OpenAIMessage {
+            role: "system".to_string(),
+            content: system.clone(),
+        });
+    }
+    messages.push(OpenAIMessage {
+        role: "user".to_string(),
+        content: prompt.user.clone(),
+    });
+    
+    let request_body = OpenAIRequest {
+        model: model.to_string(),
+        messages,
+        // Room for a body, or for a reasoning model's <think> block
+        max_tokens: Some(1024),
+        temperature: Some(0.7),
+    };
+
+    let url = format!("{}/chat/completions", base_url);
+
+    let mut request = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .json(&request_body);
+
+    // Add Authorization header only if API key is provided
+    if let Some(key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", key));
+    }
+
+    let response = request.send().await?;
+
+    if !response.status().is_success() {
+        return Err(ApiError { request: "OpenAI API request", status: response.status() }.into());
+    }
+
+    let data: OpenAIResponse = response.json().await?;
+    
+    let commit_message = data
+        .choices
+        .and_then(|choices| choices.into_iter().next())
+        .and_then(|choice| choice.message)
+        .and_then(|message| message.content)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    Ok(commit_message)
+}
+
+/// Sends a prompt to whichever provider is configured and returns the raw reply.
+pub async fn generate(provider: &ModelProvider, prompt: &Prompt) -> Result<String, Box<dyn std::error::Error>> {
+    match provider {
+        ModelProvider::Gemini { api_key, model } => {
+            call_gemini_api(api_key, model, prompt).await
+        },
+        ModelProvider::Ollama { base_url, model } => {
+            call_ollama_api(base_url, model, prompt).await
+        },
+        ModelProvider::OpenAI { base_url, api_key, model } => {
+            call_openai_api(base_url, api_key.as_ref(), model, prompt).await
+        },
+    }
+}
+
+/// Asks the provider which models it can serve, sorted by name.
+/// The configured model is ignored; only the endpoint and key are used.
+pub async fn list_models(provider: &ModelProvider) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+
+    let mut models = match provider {
+        ModelProvider::Gemini { api_key, .. } => {
+            let mut models = Vec::new();
+            let mut page_token: Option<String> = None;
+            loop {
+                let mut request = client
+                    .get(format!("{}/models", GEMINI_API_BASE))
+                    .header("x-goog-api-key", api_key.as_str())
+                    .query(&[("pageSize", "1000")]);
+                if let Some(token) = &page_token {
+                    request = request.query(&[("pageToken", token.as_str())]);
+                }
+
+                let response = request.send().await?;
+                if !response.status().is_success() {
+                    return Err(ApiError { request: "Gemini models request", status: response.status() }.into());
+                }
+
+                let data: GeminiModelList = response.json().await?;
+                for model in data.models.unwrap_or_default() {
+                    // Embedding-only and similar models can't write commit messages
+                    if model.supported_generation_methods.iter().any(|m| m == "generateContent") {
+                        let name = model.name.strip_prefix("models/").unwrap_or(&model.name);
+                        models.push(name.to_string());
+                    }
+                }
+
+                match data.next_page_token.filter(|t| !t.is_empty()) {
+                    Some(token) => page_token = Some(token),
+                    None => break,
+                }
+            }
+            models
+        },
+        ModelProvider::Ollama { base_url, .. } => {
+            let response = client
+                .get(format!("{}/api/tags", base_url))
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(ApiError { request: "Ollama models request", status: response.status() }.into());
+            }
+
+            let data: OllamaTags = response.json().await?;
+            data.models
+                .unwrap_or_default()
+                .into_iter()
+                .map(|m| m.name)
+                .collect()
+        },
+        ModelProvider::OpenAI { base_url, api_key, .. } => {
+            let mut request = client.get(format!("{}/models", base_url));
+            if let Some(key) = api_key {
+                request = request.header("Authorization", format!("Bearer {}", key));
+            }
+
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                return Err(ApiError { request: "OpenAI models request", status: response.status() }.into());
+            }
+
+            let data: OpenAIModelList = response.json().await?;
+            data.data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|m| m.id)
+                .collect()
+        },
+    };
+
+    models.sort();
+    models.dedup();
+    Ok(models)
+}
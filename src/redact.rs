@@ -0,0 +1,51 @@
+use std::sync::{Mutex, OnceLock};
+
+// Every API key acommit has resolved so far, so debug output can be scrubbed
+// even when a key ends up inside an error message or a URL.
+static SECRETS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+
+fn secrets() -> &'static Mutex<Vec<String>> {
+    SECRETS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Remembers a secret so that `redact` masks it in any later output.
+pub fn register(secret: &str) {
+    if secret.is_empty() {
+        return;
+    }
+    let mut secrets = secrets().lock().unwrap_or_else(|e| e.into_inner());
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_string());
+    }
+}
+
+/// Returns a display form of a secret that keeps at most the first four
+/// characters, which is enough to tell two keys apart.
+pub fn mask(secret: &str) -> String {
+    if secret.chars().count() <= 8 {
+        return "****".to_string();
+    }
+    let prefix: String = secret.chars().take(4).collect();
+    format!("{}****", prefix)
+}
+
+/// Debug form of an optional secret, e.g. `Some("sk-a****")` or `None`.
+pub fn mask_opt(secret: Option<&str>) -> String {
+    match secret {
+        Some(s) => format!("Some({:?})", mask(s)),
+        None => "None".to_string(),
+    }
+}
+
+/// Replaces every registered secret in `text` with its masked form.
+pub fn redact(text: &str) -> String {
+    let secrets = secrets().lock().unwrap_or_else(|e| e.into_inner());
+    let mut text = text.to_string();
+    // Longest first, so a key that contains another key is masked as a whole
+    let mut sorted: Vec<&String> = secrets.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    for secret in sorted {
+        text = text.replace(secret.as_str(), &mask(secret));
+    }
+    text
+}
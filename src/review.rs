@@ -0,0 +1,180 @@
+//! AI review of the changes about to be committed: `acommit review`, and the
+//! optional check before message generation.
+
+use serde::Serialize;
+use serde_json::Value;
+use crate::config::{Config, Severity};
+use crate::conventional;
+use crate::prompt::{self, Changes};
+use crate::providers::{self, ModelProvider, Prompt};
+use crate::redact;
+
+/// Something the model thinks should be looked at before committing.
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub file: String,
+    /// Line in the new version of the file, when the model could tell.
+    pub line: Option<u64>,
+    pub severity: Severity,
+    pub comment: String,
+}
+
+/// `acommit review [--json] [--block]`
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let json = args.iter().any(|a| a == "--json");
+    let settings = crate::parse_args(args).await?;
+
+    let changes = Changes::collect(true)?;
+    if changes.is_empty() {
+        println!("✅ No changes to review");
+        return Ok(());
+    }
+
+    eprintln!("🔍 Reviewing the changes with AI...");
+    let findings = review(&settings.provider, &settings.config, &changes).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else {
+        print_findings(&findings);
+    }
+
+    if args.iter().any(|a| a == "--block") {
+        let level = block_level(&settings.config).unwrap_or(Severity::High);
+        let blocking = count_blocking(&findings, level);
+        if blocking > 0 {
+            return Err(format!("The review found {} issue(s) of {} severity or worse", blocking, severity_name(level)).into());
+        }
+    }
+    Ok(())
+}
+
+/// Whether to review before generating the message: `--review`, or the
+/// config's `review.enabled` unless `--no-review` is given.
+pub fn enabled(args: &[String], config: &Config) -> bool {
+    if args.iter().any(|a| a == "--no-review") {
+        return false;
+    }
+    args.iter().any(|a| a == "--review") || config.review.as_ref().is_some_and(|r| r.enabled)
+}
+
+/// The review before a commit. Fails when findings reach `review.block_on`;
+/// a review that can't be done only warns, it never blocks the commit.
+pub async fn gate(provider: &ModelProvider, config: &Config, changes: &Changes) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔍 Reviewing the changes with AI...");
+    match review(provider, config, changes).await {
+        Ok(findings) => {
+            print_findings(&findings);
+            let Some(level) = block_level(config) else {
+                return Ok(());
+            };
+            let blocking = count_blocking(&findings, level);
+            if blocking > 0 {
+                return Err(format!(
+                    "The review found {} issue(s) of {} severity or worse; fix them or skip the review with --no-review",
+                    blocking, severity_name(level)
+                ).into());
+            }
+            Ok(())
+        },
+        Err(e) => {
+            println!("⚠️  Review failed, carrying on without it: {}", redact::redact(&e.to_string()));
+            Ok(())
+        },
+    }
+}
+
+fn block_level(config: &Config) -> Option<Severity> {
+    config.review.as_ref().and_then(|r| r.block_on)
+}
+
+fn count_blocking(findings: &[Finding], level: Severity) -> usize {
+    findings.iter().filter(|f| f.severity >= level).count()
+}
+
+/// Asks the model to review `changes`.
+pub async fn review(provider: &ModelProvider, config: &Config, changes: &Changes) -> Result<Vec<Finding>, Box<dyn std::error::Error>> {
+    let answer = providers::generate(provider, &review_prompt(config, changes)).await?;
+    let mut findings = parse_findings(&answer).ok_or("The model's review wasn't the expected JSON")?;
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    Ok(findings)
+}
+
+fn review_prompt(config: &Config, changes: &Changes) -> Prompt {
+    Prompt::user(format!(
+        "Review these changes before they are committed. Look for:\n\
+        - Likely bugs: wrong conditions, off-by-one errors, unhandled errors, broken edge cases\n\
+        - Security problems, such as secrets or injection\n\
+        - Leftover debug output (print statements, console.log, dbg!) and commented-out code\n\
+        - New TODO and FIXME comments\n\
+        - New behaviour without tests\n\n\
+        Changed files:\n{}\n\nDiff:\n{}\n\n\
+        Answer with a JSON array of findings and nothing else, [] if there are none. Each finding is an object with:\n\
+        - \"file\": the file's path\n\
+        - \"line\": the line in the new version of the file, from the hunk headers, or null\n\
+        - \"severity\": \"high\" for bugs and security problems, \"medium\" for debug output and missing tests, \"low\" for TODOs and minor issues\n\
+        - \"comment\": one sentence in {} saying what is wrong\n\
+        Only report real problems in the changed lines, not style preferences.",
+        changes.files.trim(), prompt::truncate_diff(&changes.diff), prompt::language(config)
+    ))
+}
+
+/// The findings in the model's answer. Models vary in how they spell
+/// fields, so this is forgiving about everything but the comment.
+fn parse_findings(answer: &str) -> Option<Vec<Finding>> {
+    let answer = conventional::strip_think_blocks(answer);
+    let start = answer.find('[')?;
+    let end = answer.rfind(']')?;
+    let items: Vec<Value> = serde_json::from_str(answer.get(start..=end)?).ok()?;
+
+    let mut findings = Vec::new();
+    for item in items {
+        let Some(comment) = item["comment"].as_str().filter(|c| !c.trim().is_empty()) else {
+            continue;
+        };
+        let line = match &item["line"] {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        let severity = match item["severity"].as_str().unwrap_or("").to_lowercase().as_str() {
+            "high" | "critical" | "error" => Severity::High,
+            "medium" | "warning" => Severity::Medium,
+            _ => Severity::Low,
+        };
+        findings.push(Finding {
+            file: item["file"].as_str().unwrap_or("").to_string(),
+            line,
+            severity,
+            comment: comment.trim().to_string(),
+        });
+    }
+    Some(findings)
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+    }
+}
+
+fn print_findings(findings: &[Finding]) {
+    if findings.is_empty() {
+        println!("✅ The review found no problems");
+        return;
+    }
+    println!("📋 The review found {} issue(s):", findings.len());
+    for finding in findings {
+        let icon = match finding.severity {
+            Severity::High => "🔴",
+            Severity::Medium => "🟡",
+            Severity::Low => "🔵",
+        };
+        let location = match finding.line {
+            Some(line) => format!("{}:{}", finding.file, line),
+            None => finding.file.clone(),
+        };
+        println!("  {} {:<6} {}  {}", icon, severity_name(finding.severity), location, finding.comment);
+    }
+}
@@ -0,0 +1,222 @@
+//! `acommit reword <range>`: new messages for a series of commits, written
+//! back without an interactive rebase.
+
+use std::collections::HashMap;
+use dialoguer::{Editor, Select};
+use crate::amend;
+use crate::git;
+use crate::lint;
+use crate::prompt::{self, Changes};
+use crate::redact;
+use crate::ticket;
+use crate::trailers;
+
+const USAGE: &str = "Usage: acommit reword <range> [--force] (e.g. main..HEAD, or HEAD~5 for HEAD~5..HEAD)";
+
+enum Choice {
+    Use(String),
+    Keep,
+    Abort,
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let range = match args.get(2).filter(|a| !a.starts_with("--")) {
+        Some(range) if range.contains("...") => return Err(USAGE.into()),
+        Some(range) if range.contains("..") => range.clone(),
+        Some(rev) => format!("{}..HEAD", rev),
+        None => return Err(USAGE.into()),
+    };
+    let force = args.iter().any(|a| a == "--force");
+
+    // Only the current branch is rewritten, by moving it to the new commits
+    let (_, tip) = range.split_once("..").unwrap_or_default();
+    let tip = if tip.is_empty() { "HEAD" } else { tip };
+    if rev_parse(tip)? != rev_parse("HEAD")? {
+        return Err(format!("The range must end at HEAD, as reword rewrites the current branch ({} is not HEAD)", tip).into());
+    }
+
+    let commits: Vec<String> = git::git(&["rev-list", "--reverse", "--topo-order", &range])?
+        .lines()
+        .map(String::from)
+        .collect();
+    if commits.is_empty() {
+        return Err(format!("No commits in {}", range).into());
+    }
+
+    // Side branches merged into the range can be published even when the
+    // oldest commit isn't, so every commit is checked
+    let upstream = git::git(&["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .ok()
+        .map(|u| u.trim().to_string());
+    let mut unpublished_args = vec!["rev-list", range.as_str(), "--not", "--remotes"];
+    if let Some(upstream) = &upstream {
+        unpublished_args.push(upstream);
+    }
+    let unpublished = git::git(&unpublished_args)?;
+    let published: Vec<&String> = commits.iter().filter(|c| !unpublished.lines().any(|u| u == c.as_str())).collect();
+    if let Some(first) = published.first().filter(|_| !force) {
+        return Err(format!(
+            "{} commit(s) in {} are already pushed ({} is on {}); rewording would rewrite published history (use --force to do it anyway)",
+            published.len(), range, git::short(first), published_on(first, upstream.as_deref())?.join(", ")
+        ).into());
+    }
+
+    let settings = crate::parse_args(args).await?;
+    let conventions = settings.config.conventions();
+    let ticket = ticket::resolve(args, &settings.config)?;
+    let prefix_ticket = ticket::prefix(&settings.config, ticket.as_deref());
+
+    println!("✏️  Rewording {} commit(s) with {}", commits.len(), settings.provider.model());
+    let mut messages: HashMap<String, String> = HashMap::new();
+    for (i, commit) in commits.iter().enumerate() {
+        let old_message = raw_message(commit)?;
+        println!("\n[{}/{}] {} {}", i + 1, commits.len(), git::short(commit), old_message.lines().next().unwrap_or(""));
+
+        let parents = parents(commit)?;
+        if parents.len() > 1 {
+            println!("↪️  Merge commit, keeping its message");
+            continue;
+        }
+        let base = match parents.first() {
+            Some(parent) => parent.clone(),
+            None => git::empty_tree()?,
+        };
+
+        let changes = Changes::between(&base, commit)?;
+        let generated = async {
+            let prompt = prompt::commit_prompt(&settings.config, &changes, ticket.as_deref())?;
+            let message = crate::generate_commit_message(&settings.provider, &prompt, &conventions, prefix_ticket).await?;
+            // Sign-offs and co-authors of the original stay
+            trailers::append(&message, &trailers::parse(&old_message)?)
+        };
+        let new_message = match generated.await {
+            Ok(message) => message,
+            Err(e) => {
+                println!("⚠️  Could not generate a message, keeping the old one: {}", redact::redact(&e.to_string()));
+                continue;
+            },
+        };
+
+        println!();
+        amend::print_side_by_side(&old_message, &new_message);
+        println!();
+        match choose(&new_message)? {
+            Choice::Use(message) => {
+                messages.insert(commit.clone(), message);
+            },
+            Choice::Keep => {},
+            Choice::Abort => {
+                println!("❌ Reword cancelled, history left as it was");
+                return Ok(());
+            },
+        }
+    }
+
+    if messages.is_empty() {
+        println!("✅ No message changed, history left as it was");
+        return Ok(());
+    }
+
+    let old_head = rev_parse("HEAD")?;
+    let new_head = rewrite(&commits, &messages)?;
+    git::git(&["update-ref", "-m", "acommit reword", "HEAD", &new_head, &old_head])?;
+    println!("\n✅ Reworded {} commit(s)", messages.len());
+    println!("💡 The previous tip was {}; `git reset --keep {}` undoes this", git::short(&old_head), git::short(&old_head));
+    Ok(())
+}
+
+fn choose(new_message: &str) -> Result<Choice, Box<dyn std::error::Error>> {
+    let choices = ["Use the new message", "Edit the new message", "Keep the old message", "Abort without rewriting"];
+    Ok(match Select::new().with_prompt("Which message?").items(&choices).default(0).interact()? {
+        0 => Choice::Use(new_message.to_string()),
+        1 => match Editor::new().edit(new_message)? {
+            Some(edited) => {
+                let edited = lint::strip_comments(&edited);
+                if edited.is_empty() { Choice::Keep } else { Choice::Use(edited) }
+            },
+            // Closed without saving
+            None => Choice::Use(new_message.to_string()),
+        },
+        2 => Choice::Keep,
+        _ => Choice::Abort,
+    })
+}
+
+/// Recreates `commits` (oldest first) with the new `messages`, keeping trees,
+/// authors and dates. Commits before the first change are reused as they are.
+/// Returns the new tip.
+fn rewrite(commits: &[String], messages: &HashMap<String, String>) -> Result<String, Box<dyn std::error::Error>> {
+    let mut rewritten: HashMap<String, String> = HashMap::new();
+    let mut signed = false;
+
+    for commit in commits {
+        let old_parents = parents(commit)?;
+        let new_parents: Vec<String> = old_parents
+            .iter()
+            .map(|p| rewritten.get(p).cloned().unwrap_or_else(|| p.clone()))
+            .collect();
+        if new_parents == old_parents && !messages.contains_key(commit) {
+            rewritten.insert(commit.clone(), commit.clone());
+            continue;
+        }
+
+        let info = git::git(&["show", "-s", "--date=raw", "--format=%T%n%an%n%ae%n%ad", commit])?;
+        let info: Vec<&str> = info.lines().collect();
+        let [tree, name, email, date] = info[..] else {
+            return Err(format!("Could not read commit {}", git::short(commit)).into());
+        };
+        let message = match messages.get(commit) {
+            Some(message) => format!("{}\n", message.trim_end()),
+            None => raw_message(commit)?,
+        };
+        signed |= git::git(&["cat-file", "commit", commit])?.contains("\ngpgsig ");
+
+        let env = [
+            ("GIT_AUTHOR_NAME", name.to_string()),
+            ("GIT_AUTHOR_EMAIL", email.to_string()),
+            ("GIT_AUTHOR_DATE", date.to_string()),
+        ];
+        let new_commit = git::commit_tree(tree, &new_parents, &message, &env)?;
+        rewritten.insert(commit.clone(), new_commit);
+    }
+
+    if signed {
+        println!("⚠️  Signatures of the rewritten commits were dropped; re-sign them if needed");
+    }
+    let tip = commits.last().ok_or("No commits to rewrite")?;
+    Ok(rewritten[tip].clone())
+}
+
+/// The message exactly as stored in the commit.
+fn raw_message(commit: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let object = git::git(&["cat-file", "commit", commit])?;
+    Ok(object.split_once("\n\n").map(|(_, message)| message.to_string()).unwrap_or_default())
+}
+
+fn parents(commit: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(git::git(&["show", "-s", "--format=%P", commit])?
+        .split_whitespace()
+        .map(String::from)
+        .collect())
+}
+
+/// The remote branches, and the upstream, that contain `commit`.
+fn published_on(commit: &str, upstream: Option<&str>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut branches: Vec<String> = git::git(&["branch", "-r", "--contains", commit])?
+        .lines()
+        .map(str::trim)
+        .filter(|r| !r.contains(" -> "))
+        .map(String::from)
+        .collect();
+    if let Some(upstream) = upstream {
+        let contains = git::git(&["merge-base", "--is-ancestor", commit, upstream]).is_ok();
+        if contains && !branches.iter().any(|b| b == upstream) {
+            branches.push(upstream.to_string());
+        }
+    }
+    Ok(branches)
+}
+
+fn rev_parse(rev: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(git::git(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])?.trim().to_string())
+}
@@ -0,0 +1,258 @@
+use std::fs;
+use dialoguer::{Confirm, Input, MultiSelect, Password, Select};
+use crate::config::{self, Config, ProviderConfig};
+use crate::interpolate;
+use crate::providers::{self, ModelProvider, Prompt};
+use crate::redact;
+
+const PROVIDERS: [(&str, &str); 3] = [
+    ("gemini", "Gemini"),
+    ("ollama", "Ollama"),
+    ("openai", "OpenAI"),
+];
+
+pub async fn setup_config() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🎯 Welcome to acommit setup!");
+    println!();
+
+    // Choose where the config lives
+    let repo_path = config::repo_config_path();
+    let global_path = config::global_config_path();
+    let mut locations = vec![format!("This repository ({})", repo_path.display())];
+    if let Some(path) = &global_path {
+        locations.push(format!("Global, for all repositories ({})", path.display()));
+    }
+    let location = Select::new()
+        .with_prompt("Where should the configuration be saved?")
+        .items(&locations)
+        .default(0)
+        .interact()?;
+    let path = match (location, global_path) {
+        (1, Some(global)) => global,
+        _ => repo_path,
+    };
+
+    // Existing settings become the defaults, so re-running setup only changes what you touch
+    let mut config = if path.exists() {
+        println!("📂 Editing existing configuration at {}", path.display());
+        config::load_config_raw(&path)?
+    } else {
+        Config {
+            default_provider: "ollama".to_string(),
+            ..Default::default()
+        }
+    };
+
+    let labels: Vec<String> = PROVIDERS
+        .iter()
+        .map(|(name, label)| match config.provider_config(name) {
+            Some(section) => format!("{} (configured: {})", label, section.model),
+            None => label.to_string(),
+        })
+        .collect();
+    let selected = MultiSelect::new()
+        .with_prompt("Which providers do you want to configure? (space to select, enter to confirm)")
+        .items(&labels)
+        .interact()?;
+
+    for index in selected {
+        let (name, label) = PROVIDERS[index];
+        println!("\n📝 Configuring {}:", label);
+        let existing = config.provider_config(name).cloned();
+        let section = configure_provider(name, existing).await?;
+        if let Some(slot) = config.provider_config_mut(name) {
+            *slot = Some(section);
+        }
+    }
+
+    let configured: Vec<(&str, &str)> = PROVIDERS
+        .iter()
+        .copied()
+        .filter(|(name, _)| config.provider_config(name).is_some())
+        .collect();
+    if configured.is_empty() {
+        return Err("No provider configured; nothing was saved".into());
+    }
+
+    println!();
+    let default_index = configured
+        .iter()
+        .position(|(name, _)| *name == config.default_provider)
+        .unwrap_or(0);
+    let configured_labels: Vec<&str> = configured.iter().map(|(_, label)| *label).collect();
+    let default_provider_selection = Select::new()
+        .with_prompt("Choose your default AI provider")
+        .items(&configured_labels)
+        .default(default_index)
+        .interact()?;
+    config.default_provider = configured[default_provider_selection].0.to_string();
+
+    config.verbose = Confirm::new()
+        .with_prompt("Enable verbose mode by default?")
+        .default(config.verbose)
+        .interact()?;
+
+    // Make sure the default provider actually works before saving
+    if Confirm::new()
+        .with_prompt("Run a test generation with the default provider?")
+        .default(true)
+        .interact()? {
+        if let Err(e) = test_generation(&config).await {
+            println!("❌ Test generation failed: {}", redact::redact(&e.to_string()));
+            if !Confirm::new()
+                .with_prompt("Save the configuration anyway?")
+                .default(false)
+                .interact()? {
+                println!("❌ Setup cancelled, nothing was saved");
+                return Ok(());
+            }
+        }
+    }
+
+    // Generate config file
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let config_json = serde_json::to_string_pretty(&config)?;
+    fs::write(&path, config_json + "\n")?;
+
+    println!("\n✅ Configuration saved to {}!", path.display());
+    println!("You can now run: acommit");
+
+    Ok(())
+}
+
+async fn configure_provider(name: &str, existing: Option<ProviderConfig>) -> Result<ProviderConfig, Box<dyn std::error::Error>> {
+    let mut section = existing.unwrap_or_else(|| match name {
+        "gemini" => ProviderConfig {
+            model: "gemini-2.5-flash-lite".to_string(),
+            api_key: None,
+            url: None,
+        },
+        "ollama" => ProviderConfig {
+            model: "llama3.2:3b".to_string(),
+            api_key: None,
+            url: Some("http://localhost:11434".to_string()),
+        },
+        _ => ProviderConfig {
+            model: "bitnet-model".to_string(),
+            api_key: None,
+            url: Some("http://localhost:7777/v1".to_string()),
+        },
+    });
+
+    match name {
+        "gemini" => {
+            section.api_key = ask_api_key("Gemini", "GEMINI_API_KEY", section.api_key.take())?;
+        },
+        "ollama" => {
+            let ollama_url: String = Input::new()
+                .with_prompt("Ollama URL")
+                .default(section.url.clone().unwrap_or_else(|| "http://localhost:11434".to_string()))
+                .interact_text()?;
+            section.url = Some(ollama_url);
+        },
+        _ => {
+            let openai_url: String = Input::new()
+                .with_prompt("OpenAI-compatible API URL")
+                .default(section.url.clone().unwrap_or_else(|| "http://localhost:7777/v1".to_string()))
+                .interact_text()?;
+            section.url = Some(openai_url);
+            section.api_key = ask_api_key("OpenAI", "OPENAI_API_KEY", section.api_key.take())?;
+        },
+    }
+
+    section.model = pick_model(name, &section).await?;
+    Ok(section)
+}
+
+fn ask_api_key(label: &str, env_var: &str, existing: Option<String>) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if let Some(key) = existing {
+        let shown = if key.contains("${") { key.clone() } else { redact::mask(&key) };
+        if Confirm::new()
+            .with_prompt(format!("Keep the existing {} API key ({})?", label, shown))
+            .default(true)
+            .interact()? {
+            return Ok(Some(key));
+        }
+    }
+
+    if !Confirm::new()
+        .with_prompt(format!("Do you want to set {} API key in config? (otherwise {} is used)", label, env_var))
+        .default(false)
+        .interact()? {
+        return Ok(None);
+    }
+
+    println!("💡 Tip: enter ${{{}}} to keep the key itself out of the file", env_var);
+    let api_key = Password::new()
+        .with_prompt(format!("Enter {} API key", label))
+        .interact()?;
+    Ok(Some(api_key))
+}
+
+/// Offers the provider's model list, falling back to free text when the
+/// provider can't be reached.
+async fn pick_model(name: &str, section: &ProviderConfig) -> Result<String, Box<dyn std::error::Error>> {
+    println!("🔎 Fetching available models...");
+    let models = match resolve_section(name, section) {
+        Ok(provider) => providers::list_models(&provider).await,
+        Err(e) => Err(e),
+    };
+
+    match models {
+        Ok(models) if !models.is_empty() => {
+            let mut items = models.clone();
+            items.push("Other (type a model name)".to_string());
+            let default_index = models.iter().position(|m| *m == section.model).unwrap_or(0);
+            let choice = Select::new()
+                .with_prompt("Model")
+                .items(&items)
+                .default(default_index)
+                .interact()?;
+            if choice < models.len() {
+                return Ok(models[choice].clone());
+            }
+        },
+        Ok(_) => println!("⚠️  The provider reported no models"),
+        Err(e) => println!("⚠️  Could not list models: {}", redact::redact(&e.to_string())),
+    }
+
+    let model: String = Input::new()
+        .with_prompt("Model name")
+        .default(section.model.clone())
+        .interact_text()?;
+    Ok(model)
+}
+
+/// Builds a provider from a config section, expanding `${VAR}`s the same way
+/// loading the file would.
+fn resolve_section(name: &str, section: &ProviderConfig) -> Result<ModelProvider, Box<dyn std::error::Error>> {
+    let mut config = Config {
+        default_provider: name.to_string(),
+        ..Default::default()
+    };
+    if let Some(slot) = config.provider_config_mut(name) {
+        *slot = Some(section.clone());
+    }
+    resolve_provider(&config, name)
+}
+
+fn resolve_provider(config: &Config, name: &str) -> Result<ModelProvider, Box<dyn std::error::Error>> {
+    let mut raw = serde_json::to_value(config)?;
+    interpolate::interpolate_value(&mut raw)?;
+    let config: Config = serde_json::from_value(raw)?;
+    let (provider, _) = config::config_to_provider(&config, Some(name))?;
+    Ok(provider)
+}
+
+async fn test_generation(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = resolve_provider(config, &config.default_provider)?;
+    println!("🤖 Asking {} ({}) for a sample commit message...", provider.name(), provider.model());
+    let message = providers::generate(
+        &provider,
+        &Prompt::user("Generate a git commit message in conventional commits format for adding a README file. Only return the commit message, nothing else"),
+    ).await?;
+    println!("📋 Sample: {}", crate::conventional::clean_output(&message));
+    Ok(())
+}
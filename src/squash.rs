@@ -0,0 +1,70 @@
+//! `acommit squash`: one message for everything a branch did since it left
+//! the branch it will be merged into, optionally squashing it into one commit.
+
+use crate::git;
+use crate::prompt::Changes;
+use crate::trailers;
+
+pub async fn run(args: &[String], git_tail: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = crate::parse_args(args).await?;
+
+    let onto = match crate::value_arg(args, "--onto")? {
+        Some(onto) => onto,
+        None => git::default_branch().ok_or("Could not tell which branch to squash onto, pass it with --onto <BRANCH>")?,
+    };
+    let base = git::git(&["merge-base", &onto, "HEAD"])
+        .map_err(|_| format!("HEAD and {} have no common history", onto))?
+        .trim()
+        .to_string();
+
+    let messages = git::commit_messages(&format!("{}..HEAD", base))?;
+    if messages.is_empty() {
+        return Err(format!("Nothing to squash: HEAD has no commits since it left {}", onto).into());
+    }
+
+    println!("🧩 {} commit(s) since {} ({}):", messages.len(), onto, git::short(&base));
+    for message in &messages {
+        println!("  - {}", message.lines().next().unwrap_or(""));
+    }
+
+    let mut changes = Changes::between(&base, "HEAD")?;
+    if changes.is_empty() {
+        return Err(format!("The commits since {} cancel out, there is nothing to describe", onto).into());
+    }
+    changes.commits = messages.clone();
+
+    let commit_options = crate::commit_options(args, git_tail)?;
+
+    println!("🤖 Generating squash message with AI...");
+    // Sign-offs and co-authors of the squashed commits stay
+    let mut kept = Vec::new();
+    for old in &messages {
+        kept.extend(trailers::parse(old)?);
+    }
+    let message = crate::compose_message(&settings, args, &changes, &kept).await?;
+
+    println!("📋 Generated squash message:\n{}", message);
+    println!();
+
+    if !git::git(&["diff", "--cached", "--name-only"])?.trim().is_empty() {
+        println!("⚠️  There are staged changes, which would end up in the squashed commit; commit or unstage them to squash");
+        return Ok(());
+    }
+
+    if !crate::confirm(&format!("Squash the {} commit(s) into one with this message?", messages.len()))? {
+        println!("❌ Squash cancelled, the branch is unchanged");
+        return Ok(());
+    }
+
+    let old_head = git::git(&["rev-parse", "HEAD"])?.trim().to_string();
+    println!("💾 Squashing...");
+    git::git(&["reset", "--soft", &base])?;
+    if let Err(e) = git::commit(&message, &commit_options) {
+        // Put the branch back so the commits aren't left as staged changes
+        git::git(&["reset", "--soft", &old_head])?;
+        return Err(e);
+    }
+    println!("✅ Squashed {} commit(s) into one", messages.len());
+    println!("💡 The previous tip was {}; `git reset --keep {}` undoes this", git::short(&old_head), git::short(&old_head));
+    Ok(())
+}
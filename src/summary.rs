@@ -0,0 +1,123 @@
+//! `acommit summary`: a standup-style summary of recent commits, from the
+//! current repository or the ones listed in the config.
+
+use std::path::Path;
+use crate::conventional;
+use crate::git;
+use crate::prompt;
+use crate::providers::{self, Prompt};
+
+const DEFAULT_SINCE: &str = "yesterday";
+
+struct Activity {
+    repository: String,
+    /// One list item per commit: date, author and message.
+    commits: Vec<String>,
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let since = crate::value_arg(args, "--since")?.unwrap_or_else(|| DEFAULT_SINCE.to_string());
+    let author = crate::value_arg(args, "--author")?;
+
+    let settings = crate::parse_args(args).await?;
+
+    let configured = settings.config.summary.as_ref().map(|s| s.repositories.clone()).unwrap_or_default();
+    let repositories = if configured.is_empty() {
+        let root = git::repo_root().ok_or("Not in a git repository; run this in one, or list repositories under summary.repositories in the config")?;
+        vec![root.display().to_string()]
+    } else {
+        configured
+    };
+
+    let mut activity = Vec::new();
+    for repository in &repositories {
+        match commits(repository, &since, author.as_deref()) {
+            Ok(commits) if commits.is_empty() => {},
+            Ok(commits) => activity.push(Activity { repository: repo_name(repository), commits }),
+            Err(e) if repositories.len() > 1 => eprintln!("⚠️  Skipping {}: {}", repository, e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let total: usize = activity.iter().map(|a| a.commits.len()).sum();
+    if total == 0 {
+        println!("✅ No commits since {}", since);
+        return Ok(());
+    }
+
+    // Progress goes to stderr so stdout can be pasted as is
+    eprintln!("📊 {} commit(s) since {} in {} repositor{}", total, since, activity.len(), if activity.len() == 1 { "y" } else { "ies" });
+    eprintln!("🤖 Summarizing with AI...");
+    let prompt = summary_prompt(&settings.config, &activity, author.is_some());
+    let answer = providers::generate(&settings.provider, &prompt).await?;
+    println!("{}", conventional::strip_think_blocks(&answer).trim());
+    Ok(())
+}
+
+/// The commits in `repository` since `since`, on any branch, oldest first.
+/// `me` as the author means the repository's `user.email`.
+fn commits(repository: &str, since: &str, author: Option<&str>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    if !Path::new(repository).is_dir() {
+        return Err("no such directory".into());
+    }
+    let since = format!("--since={}", since);
+    let mut args = vec!["-C", repository, "log", "--all", "--no-merges", "--reverse", "--date=short", "--format=%ad%x1f%an%x1f%B%x00", since.as_str()];
+
+    let author = match author {
+        Some("me") => {
+            let email = git::git(&["-C", repository, "config", "user.email"])
+                .map_err(|_| "--author me needs user.email to be set")?;
+            Some(format!("--author={}", email.trim()))
+        },
+        Some(author) => Some(format!("--author={}", author)),
+        None => None,
+    };
+    if let Some(author) = &author {
+        args.push(author);
+    }
+
+    let log = git::git(&args)?;
+    Ok(log
+        .split('\0')
+        .map(str::trim)
+        .filter(|record| !record.is_empty())
+        .filter_map(|record| {
+            let mut fields = record.splitn(3, '\x1f');
+            let (date, name, message) = (fields.next()?, fields.next()?, fields.next()?);
+            Some(prompt::commit_item(&format!("[{}, {}] {}", date, name, message.trim())))
+        })
+        .collect())
+}
+
+fn repo_name(repository: &str) -> String {
+    let path = Path::new(repository);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| repository.to_string())
+}
+
+fn summary_prompt(config: &crate::config::Config, activity: &[Activity], single_author: bool) -> Prompt {
+    let mut commits = String::new();
+    for repo in activity {
+        commits.push_str(&format!("Repository {}:\n{}\n\n", repo.repository, repo.commits.join("\n")));
+    }
+    let grouping = if activity.len() > 1 {
+        "- Group the bullets under the repository names"
+    } else {
+        "- Don't mention the repository"
+    };
+    let people = if single_author {
+        "- The work is all by one person; don't name them"
+    } else {
+        "- Say who did what when several people are involved"
+    };
+    Prompt::user(format!(
+        "Summarize this recent work for a standup meeting, in {}:\n\n{}\
+        Rules:\n\
+        - A short bullet list of what was done, by outcome rather than by commit\n\
+        - Merge related commits and leave out trivial ones (typos, formatting, merges)\n\
+        {}\n\
+        {}\n\
+        - Only return the bullet list, nothing else",
+        prompt::language(config), commits, grouping, people
+    ))
+}
@@ -0,0 +1,58 @@
+//! Ticket IDs, from `--ticket` or the branch name, and where they go in
+//! the message.
+
+use regex::Regex;
+use crate::config::{Config, TicketPlacement};
+use crate::git;
+
+// JIRA style keys, as in `feature/PROJ-482-export-csv`
+const DEFAULT_PATTERN: &str = r"[A-Z][A-Z0-9_]*-\d+";
+
+/// `--ticket <ID>`, otherwise the ID in the branch name.
+pub fn resolve(args: &[String], config: &Config) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match crate::ticket_arg(args)? {
+        Some(ticket) => Ok(Some(ticket)),
+        None => from_branch(config),
+    }
+}
+
+/// The ticket ID in the current branch's name. Only looked for when the
+/// config has a `ticket` section or the conventions require a ticket prefix.
+pub fn from_branch(config: &Config) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    if config.ticket.is_none() && !config.conventions().ticket_prefix {
+        return Ok(None);
+    }
+    match git::current_branch() {
+        Some(branch) => find(config, &branch),
+        None => Ok(None),
+    }
+}
+
+/// The first ticket ID in `text`, by the configured pattern.
+pub fn find(config: &Config, text: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let pattern = config
+        .ticket
+        .as_ref()
+        .and_then(|t| t.pattern.as_deref())
+        .unwrap_or(DEFAULT_PATTERN);
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid ticket pattern '{}': {}", pattern, e))?;
+
+    Ok(regex.captures(text).and_then(|captures| {
+        let id = captures.get(1).or_else(|| captures.get(0))?;
+        Some(id.as_str().to_string())
+    }))
+}
+
+fn placement(config: &Config) -> TicketPlacement {
+    config.ticket.as_ref().map(|t| t.placement).unwrap_or_default()
+}
+
+/// The ticket to put in front of the subject, if tickets go there.
+pub fn prefix<'a>(config: &Config, ticket: Option<&'a str>) -> Option<&'a str> {
+    ticket.filter(|_| placement(config) == TicketPlacement::Prefix)
+}
+
+/// The value of the `Refs:` trailer, if tickets go there.
+pub fn trailer<'a>(config: &Config, ticket: Option<&'a str>) -> Option<&'a str> {
+    ticket.filter(|_| placement(config) == TicketPlacement::Trailer)
+}
@@ -0,0 +1,112 @@
+//! Trailers appended to generated messages: `Refs`, the config's custom
+//! ones, `Co-authored-by` and `Signed-off-by`.
+
+use crate::config::Config;
+use crate::git;
+use crate::ticket;
+
+/// A `token: value` line at the end of a commit message.
+pub type Trailer = (String, String);
+
+/// The trailers for this commit, from the arguments (`--signoff`,
+/// `--pair <ALIAS>`) and the config's `trailers` section.
+pub fn collect(args: &[String], config: &Config, ticket: Option<&str>) -> Result<Vec<Trailer>, Box<dyn std::error::Error>> {
+    let section = config.trailers.clone().unwrap_or_default();
+    let mut trailers: Vec<Trailer> = Vec::new();
+
+    if let Some(ticket) = ticket::trailer(config, ticket) {
+        trailers.push(("Refs".to_string(), ticket.to_string()));
+    }
+
+    for line in &section.custom {
+        let (token, value) = line
+            .split_once(':')
+            .filter(|(token, value)| is_token(token.trim()) && !value.trim().is_empty())
+            .ok_or_else(|| format!("Invalid trailer '{}' in config (expected 'Token: value')", line))?;
+        trailers.push((token.trim().to_string(), value.trim().to_string()));
+    }
+
+    // --pair replaces the configured pairing rather than adding to it
+    let mut pairs = pair_args(args)?;
+    if pairs.is_empty() {
+        pairs = section.pair_with.clone();
+    }
+    for pair in &pairs {
+        let co_author = match section.pairs.get(pair) {
+            Some(identity) => identity.clone(),
+            None if pair.contains('<') && pair.ends_with('>') => pair.clone(),
+            None => return Err(format!(
+                "Unknown pair '{}' (add it to trailers.pairs, or use --pair \"Name <email>\")",
+                pair
+            ).into()),
+        };
+        trailers.push(("Co-authored-by".to_string(), co_author));
+    }
+
+    if section.signoff || args.iter().any(|a| a == "--signoff") {
+        trailers.push(("Signed-off-by".to_string(), identity()?));
+    }
+
+    Ok(trailers)
+}
+
+fn is_token(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// `--pair alice --pair bob`, or `--pair alice,bob`.
+fn pair_args(args: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut pairs = Vec::new();
+    for (i, arg) in args.iter().enumerate().skip(1) {
+        let value = if let Some(value) = arg.strip_prefix("--pair=") {
+            value
+        } else if arg == "--pair" {
+            args.get(i + 1).ok_or("--pair requires a name from trailers.pairs, or \"Name <email>\"")?
+        } else {
+            continue;
+        };
+        pairs.extend(value.split(',').map(str::trim).filter(|p| !p.is_empty()).map(String::from));
+    }
+    Ok(pairs)
+}
+
+/// `Name <email>` of the committer, the way `git commit --signoff` writes it.
+fn identity() -> Result<String, Box<dyn std::error::Error>> {
+    const UNKNOWN: &str = "Could not determine your git identity for the sign-off (set user.name and user.email)";
+    let ident = git::git(&["var", "GIT_COMMITTER_IDENT"]).map_err(|_| UNKNOWN)?;
+    // The identity is followed by a timestamp and timezone
+    let end = ident.find('>').ok_or(UNKNOWN)?;
+    Ok(ident[..=end].to_string())
+}
+
+/// The trailers already at the end of `message`.
+pub fn parse(message: &str) -> Result<Vec<Trailer>, Box<dyn std::error::Error>> {
+    let output = git::git_with_input(&["interpret-trailers", "--parse", "--no-divider"], message)?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .map(|(token, value)| (token.to_string(), value.to_string()))
+        .collect())
+}
+
+/// Appends `trailers` the way `git interpret-trailers` does: into an existing
+/// trailer block, without repeating a trailer that is already there.
+pub fn append(message: &str, trailers: &[Trailer]) -> Result<String, Box<dyn std::error::Error>> {
+    if trailers.is_empty() {
+        return Ok(message.to_string());
+    }
+
+    let lines: Vec<String> = trailers
+        .iter()
+        .map(|(token, value)| format!("{}: {}", token, value))
+        .collect();
+    let mut args = vec!["interpret-trailers", "--no-divider", "--if-exists", "addIfDifferent"];
+    for line in &lines {
+        args.push("--trailer");
+        args.push(line);
+    }
+
+    // Without the blank line a lone "feat: x" subject would count as a trailer
+    let output = git::git_with_input(&args, &format!("{}\n\n", message.trim_end()))?;
+    Ok(output.trim_end().to_string())
+}